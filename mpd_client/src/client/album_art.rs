@@ -0,0 +1,325 @@
+use std::{
+    fmt,
+    future::{Future, poll_fn},
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use bytes::Bytes;
+use futures_core::Stream;
+use mpd_protocol::{
+    command::{Command as RawCommand, CommandList as RawCommandList},
+    response::ErrorCode,
+};
+use tracing::{debug, trace, warn};
+
+use super::{Client, CommandError};
+use crate::{
+    commands::{self as cmds, Command},
+    responses::{self as res, TypedResponseError},
+};
+
+/// Default binary limit of MPD.
+pub(super) const DEFAULT_BINARY_LIMIT: usize = 8192;
+
+/// Binary limit used for the duration of each album art request made by an
+/// [`AlbumArtStream`].
+const STREAM_BINARY_LIMIT: usize = 256 * 1024;
+
+type ChunkFuture =
+    Pin<Box<dyn Future<Output = Result<Option<res::AlbumArt>, CommandError>> + Send>>;
+
+/// A stream of album art data, returned by [`Client::album_art_stream`].
+///
+/// This yields the data in chunks as they are received, and ends once [`size`] bytes were
+/// yielded. If an error occurs, it is yielded and the stream ends.
+///
+/// [`size`]: AlbumArtStream::size
+pub struct AlbumArtStream {
+    client: Client,
+    uri: String,
+    embedded: bool,
+    size: usize,
+    mime: Option<String>,
+    received: usize,
+    first: Option<Bytes>,
+    pending: Option<ChunkFuture>,
+    done: bool,
+}
+
+impl AlbumArtStream {
+    /// The total size of the album art, in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The MIME type of the album art, if the server provided one.
+    pub fn mime(&self) -> Option<&str> {
+        self.mime.as_deref()
+    }
+
+    /// Returns `true` if the album art is embedded in the song file, and `false` if it is a
+    /// separate file.
+    pub fn is_embedded(&self) -> bool {
+        self.embedded
+    }
+
+    /// The number of bytes received so far.
+    pub fn received(&self) -> usize {
+        self.received
+    }
+
+    /// Wait for the next chunk of data.
+    ///
+    /// This is a convenience method for the [`Stream`] implementation. Returns `None` once all
+    /// data was received, or after an error.
+    pub async fn next(&mut self) -> Option<Result<Bytes, CommandError>> {
+        poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+}
+
+impl Stream for AlbumArtStream {
+    type Item = Result<Bytes, CommandError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if let Some(first) = this.first.take() {
+            return Poll::Ready(Some(Ok(first)));
+        }
+
+        if this.done || this.received >= this.size {
+            return Poll::Ready(None);
+        }
+
+        let pending = this.pending.get_or_insert_with(|| {
+            let client = this.client.clone();
+            let uri = this.uri.clone();
+            let embedded = this.embedded;
+            let offset = this.received;
+
+            Box::pin(async move { client.album_art_chunk(&uri, embedded, offset).await })
+        });
+
+        let result = ready!(pending.as_mut().poll(cx));
+        this.pending = None;
+
+        match result {
+            Ok(Some(art)) if !art.data.is_empty() => {
+                this.received += art.data.len();
+                trace!(received = art.data.len(), progress = this.received);
+                Poll::Ready(Some(Ok(art.data.freeze())))
+            }
+            Ok(_) => {
+                warn!(progress = this.received, "incomplete cover art response");
+                this.done = true;
+                Poll::Ready(Some(Err(TypedResponseError::missing("binary").into())))
+            }
+            Err(e) => {
+                this.done = true;
+                Poll::Ready(Some(Err(e)))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for AlbumArtStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlbumArtStream")
+            .field("uri", &self.uri)
+            .field("embedded", &self.embedded)
+            .field("size", &self.size)
+            .field("mime", &self.mime)
+            .field("received", &self.received)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Load the album art for the given URI as a [stream][AlbumArtStream] of chunks.
+    ///
+    /// Like [`Client::album_art`], this prefers embedded album art and falls back to a separate
+    /// file. The first chunk is loaded before this returns, so the total size and the MIME type
+    /// are known up front, which allows displaying progress. Loading stops as soon as the stream is
+    /// dropped.
+    ///
+    /// If the server supports it, the [binary limit][cmds::SetBinaryLimit] is raised for each
+    /// request to reduce the number of round-trips, and restored immediately afterwards. It is
+    /// restored to the limit applied while connecting (see [`ClientConfig::binary_limit`]), so
+    /// changing it by sending the `SetBinaryLimit` command directly is undone by this.
+    ///
+    /// [`ClientConfig::binary_limit`]: super::ClientConfig::binary_limit
+    ///
+    /// ```no_run
+    /// # async fn example(client: mpd_client::Client) -> Result<(), mpd_client::client::CommandError> {
+    /// if let Some(mut art) = client.album_art_stream("foo/bar.flac", Some(4 << 20)).await? {
+    ///     while let Some(chunk) = art.next().await {
+    ///         let chunk = chunk?;
+    ///         println!("{}/{} bytes", art.received(), art.size());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Return value
+    ///
+    /// A return value of `None` indicates that no album art for the given URI was found.
+    ///
+    /// # Errors
+    ///
+    /// This returns errors in the same conditions as [`Client::command`]. If `max_size` is given
+    /// and the album art is larger, this returns a [`TooLarge`][CommandError::TooLarge] error
+    /// without loading the rest of it.
+    #[tracing::instrument(skip(self))]
+    pub async fn album_art_stream(
+        &self,
+        uri: &str,
+        max_size: Option<usize>,
+    ) -> Result<Option<AlbumArtStream>, CommandError> {
+        debug!("loading album art");
+
+        let mut embedded = true;
+
+        let art = if self.supports_embedded_album_art() {
+            match self.album_art_chunk(uri, true, 0).await {
+                Ok(Some(art)) => Some(art),
+                Ok(None) => {
+                    debug!("readpicture command gave no result, falling back");
+                    None
+                }
+                // The server version may be unknown
+                Err(e) if e.error_code() == Some(ErrorCode::Unknown) => {
+                    debug!("readpicture command unsupported, falling back");
+                    None
+                }
+                Err(e) => return Err(e),
+            }
+        } else {
+            debug!("readpicture command unsupported by server version, falling back");
+            None
+        };
+
+        let art = match art {
+            Some(art) => art,
+            None => {
+                embedded = false;
+
+                match self.album_art_chunk(uri, false, 0).await? {
+                    Some(art) => art,
+                    None => {
+                        debug!("no embedded or separate album art found");
+                        return Ok(None);
+                    }
+                }
+            }
+        };
+
+        debug!(length = art.size, mime = ?art.mime, embedded, "found album art");
+
+        if let Some(max_size) = max_size
+            && art.size > max_size
+        {
+            debug!(max_size, "album art is too large");
+            return Err(CommandError::TooLarge {
+                size: art.size,
+                max_size,
+            });
+        }
+
+        Ok(Some(AlbumArtStream {
+            client: self.clone(),
+            uri: uri.to_owned(),
+            embedded,
+            size: art.size,
+            mime: art.mime,
+            received: art.data.len(),
+            first: Some(art.data.freeze()),
+            pending: None,
+            done: false,
+        }))
+    }
+
+    /// Load a single chunk of album art, temporarily raising the binary limit if possible.
+    async fn album_art_chunk(
+        &self,
+        uri: &str,
+        embedded: bool,
+        offset: usize,
+    ) -> Result<Option<res::AlbumArt>, CommandError> {
+        let command = if embedded {
+            cmds::AlbumArtEmbedded::new(uri).offset(offset).command()
+        } else {
+            cmds::AlbumArt::new(uri).offset(offset).command()
+        };
+
+        let Some((raise, restore)) = self.binary_limit_commands() else {
+            let frame = self.raw_command(command).await?;
+            return Ok(res::AlbumArt::from_frame(frame)?);
+        };
+
+        let list = RawCommandList::new(raise)
+            .command(command.clone())
+            .command(restore.clone());
+
+        let frame = match self.raw_command_list(list).await {
+            Ok(mut frames) => frames.swap_remove(1),
+            Err(CommandError::ErrorResponse {
+                error,
+                mut succesful_frames,
+            }) if succesful_frames.len() == 2 => {
+                // The album art was loaded, only restoring the binary limit failed
+                warn!(
+                    code = error.code,
+                    message = &*error.message,
+                    "failed to restore binary limit"
+                );
+                succesful_frames.swap_remove(1)
+            }
+            Err(CommandError::ErrorResponse {
+                mut error,
+                succesful_frames,
+            }) if succesful_frames.len() == 1 => {
+                // The command itself failed, so restoring the binary limit did not run
+                if let Err(e) = self.raw_command(restore).await {
+                    warn!(error = ?e, "failed to restore binary limit");
+                }
+
+                // Make the error refer to the single command
+                error.command_index = 0;
+
+                return Err(CommandError::ErrorResponse {
+                    error,
+                    succesful_frames: Vec::new(),
+                });
+            }
+            Err(CommandError::ErrorResponse { error, .. }) => {
+                // Raising the binary limit failed, so load the album art without it
+                warn!(
+                    code = error.code,
+                    message = &*error.message,
+                    "failed to raise binary limit"
+                );
+                self.raw_command(command).await?
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(res::AlbumArt::from_frame(frame)?)
+    }
+
+    /// Get the commands to raise the binary limit for loading album art and to restore it
+    /// afterwards, or `None` if this is not possible or necessary.
+    fn binary_limit_commands(&self) -> Option<(RawCommand, RawCommand)> {
+        let required = cmds::SetBinaryLimit(STREAM_BINARY_LIMIT).min_protocol_version()?;
+
+        if self.binary_limit >= STREAM_BINARY_LIMIT || self.version.is_none_or(|v| v < required) {
+            return None;
+        }
+
+        Some((
+            cmds::SetBinaryLimit(STREAM_BINARY_LIMIT).command(),
+            cmds::SetBinaryLimit(self.binary_limit).command(),
+        ))
+    }
+}
@@ -0,0 +1,174 @@
+use mpd_protocol::{
+    MpdProtocolError,
+    command::CommandList as RawCommandList,
+    response::{Frame, Response as RawResponse},
+};
+use tokio::sync::mpsc;
+use tracing::{error, trace};
+
+use crate::client::{CommandError, CommandResponder, DEFAULT_MAX_COMMAND_LIST_SIZE};
+
+/// Maximum number of commands combined into a single automatically created batch.
+const MAX_BATCH_LEN: usize = 128;
+
+/// Responder for a command list sent by the run loop, which may be a batch made up of multiple
+/// command lists sent by different callers.
+pub(super) enum Responder {
+    Single(CommandResponder),
+    /// Responders of the command lists making up the batch, with the number of commands in each.
+    Batch(Vec<(usize, CommandResponder)>),
+}
+
+impl Responder {
+    /// Send the response to the caller(s).
+    pub(super) fn send(self, response: Result<RawResponse, CommandError>) {
+        let batch = match self {
+            Responder::Single(responder) => {
+                let _ = responder.send(response);
+                return;
+            }
+            Responder::Batch(batch) => batch,
+        };
+
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                let mut batch = batch.into_iter();
+
+                if let Some((_, responder)) = batch.next() {
+                    let _ = responder.send(Err(e));
+                }
+
+                // The error (if it wasn't already `ConnectionClosed`) means the connection is
+                // unusable
+                for (_, responder) in batch {
+                    let _ = responder.send(Err(CommandError::ConnectionClosed));
+                }
+
+                return;
+            }
+        };
+
+        let mut frames = response.into_iter();
+        let mut failed = false;
+
+        for (len, responder) in batch {
+            if failed {
+                let _ = responder.send(Err(CommandError::BatchAborted));
+                continue;
+            }
+
+            let mut successful: Vec<Frame> = Vec::with_capacity(len);
+            let mut error = None;
+
+            while successful.len() < len {
+                match frames.next() {
+                    Some(Ok(frame)) => successful.push(frame),
+                    Some(Err(mut e)) => {
+                        // Make the index relative to the caller's command list
+                        e.command_index = successful.len() as u64;
+                        error = Some(e);
+                        break;
+                    }
+                    None => break,
+                }
+            }
+
+            let response = if error.is_some() {
+                failed = true;
+                Ok(RawResponse::new(successful, error))
+            } else if successful.len() < len {
+                error!(
+                    expected = len,
+                    received = successful.len(),
+                    "batch response is missing frames"
+                );
+                failed = true;
+                Err(CommandError::Protocol(MpdProtocolError::InvalidMessage))
+            } else {
+                Ok(RawResponse::new(successful, None))
+            };
+
+            let _ = responder.send(response);
+        }
+    }
+}
+
+/// The queue of command lists waiting to be sent by the run loop.
+pub(super) struct CommandQueue {
+    receiver: mpsc::Receiver<(RawCommandList, CommandResponder)>,
+    /// A command list which did not fit into the previous batch, and is sent next.
+    deferred: Option<(RawCommandList, CommandResponder)>,
+}
+
+impl CommandQueue {
+    pub(super) fn new(receiver: mpsc::Receiver<(RawCommandList, CommandResponder)>) -> Self {
+        Self {
+            receiver,
+            deferred: None,
+        }
+    }
+
+    /// Receive the next command list. Returns `None` once all clients were dropped.
+    ///
+    /// This is cancel safe.
+    pub(super) async fn recv(&mut self) -> Option<(RawCommandList, CommandResponder)> {
+        match self.deferred.take() {
+            Some(commands) => Some(commands),
+            None => self.receiver.recv().await,
+        }
+    }
+}
+
+/// Combine the given command list with any further command lists which are immediately available
+/// from the queue, if `enabled`.
+///
+/// Command lists are only added as long as the batch stays within [`MAX_BATCH_LEN`] commands and
+/// the [default maximum size][DEFAULT_MAX_COMMAND_LIST_SIZE] of command lists. The first command
+/// list which does not fit is left in the queue for the next batch.
+pub(super) fn collect(
+    (commands, responder): (RawCommandList, CommandResponder),
+    queue: &mut CommandQueue,
+    enabled: bool,
+) -> (RawCommandList, Responder) {
+    if !enabled {
+        return (commands, Responder::Single(responder));
+    }
+
+    let mut batch_len = commands.len();
+    let mut batch_size = commands.size();
+    let mut batch = vec![(commands.len(), responder)];
+    let mut combined = commands;
+
+    while let Some((commands, responder)) = queue
+        .deferred
+        .take()
+        .or_else(|| queue.receiver.try_recv().ok())
+    {
+        if batch_len + commands.len() > MAX_BATCH_LEN
+            || batch_size + commands.size() > DEFAULT_MAX_COMMAND_LIST_SIZE
+        {
+            queue.deferred = Some((commands, responder));
+            break;
+        }
+
+        batch_len += commands.len();
+        batch_size += commands.size();
+        batch.push((commands.len(), responder));
+        combined.extend(commands);
+    }
+
+    if batch.len() == 1 {
+        let (_, responder) = batch.pop().unwrap();
+        return (combined, Responder::Single(responder));
+    }
+
+    trace!(
+        lists = batch.len(),
+        commands = batch_len,
+        size = batch_size,
+        "batching commands"
+    );
+
+    (combined, Responder::Batch(batch))
+}
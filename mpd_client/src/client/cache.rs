@@ -0,0 +1,892 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt, io,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::SystemTime,
+};
+
+use bytes::Bytes;
+use mpd_protocol::response::ErrorCode;
+use sha2::{Digest as _, Sha256};
+use tracing::{debug, trace, warn};
+
+use super::{Client, CommandError, Subsystem};
+use crate::commands as cmds;
+
+/// Default capacity of the in-memory cache, in bytes.
+const DEFAULT_MEMORY_CAPACITY: usize = 32 * 1024 * 1024;
+
+/// Default capacity of the disk cache, in bytes.
+const DEFAULT_DISK_CAPACITY: u64 = 256 * 1024 * 1024;
+
+/// Maximum number of songs and directories remembered to have no album art.
+const MAX_MISSING: usize = 4096;
+
+/// A caching layer for [`Client::album_art`].
+///
+/// Album art is cached in memory, evicting the least recently used entries once the
+/// [capacity][AlbumArtCache::memory_capacity] is exceeded, and optionally [on
+/// disk][AlbumArtCache::disk_cache].
+///
+/// Embedded album art is cached per song. Since MPD looks for separate album art files in the
+/// directory of the song, these are cached per directory, so that loading the album art for every
+/// song of an album only loads the file once. The data itself is stored by its SHA-256 digest, so
+/// identical album art embedded into multiple songs is only stored once.
+///
+/// The cache does not observe changes to the music database by itself. Pass [state
+/// changes][super::ConnectionEvent] to [`AlbumArtCache::subsystem_changed`], or call
+/// [`AlbumArtCache::invalidate`] directly.
+///
+/// ```no_run
+/// # async fn example(client: mpd_client::Client) -> Result<(), mpd_client::client::CommandError> {
+/// use mpd_client::client::AlbumArtCache;
+///
+/// let cache = AlbumArtCache::new(client)
+///     .memory_capacity(64 << 20)
+///     .disk_cache("/tmp/mpd-album-art", "localhost:6600");
+///
+/// if let Some((data, mime)) = cache.album_art("foo/bar.flac").await? {
+///     println!("{} bytes, {mime:?}", data.len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Cloning the cache is cheap, and the clones share the cached data.
+#[derive(Clone)]
+pub struct AlbumArtCache {
+    client: Client,
+    disk: Option<Arc<Disk>>,
+    disk_capacity: u64,
+    state: Arc<Mutex<State>>,
+}
+
+impl AlbumArtCache {
+    /// Create a new cache loading album art using the given client.
+    ///
+    /// By default, up to 32 MiB of album art are cached in memory, and nothing is cached on disk.
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            disk: None,
+            disk_capacity: DEFAULT_DISK_CAPACITY,
+            state: Arc::new(Mutex::new(State {
+                generation: 0,
+                songs: HashMap::new(),
+                directories: HashMap::new(),
+                missing: VecDeque::new(),
+                memory: MemoryCache::new(DEFAULT_MEMORY_CAPACITY),
+            })),
+        }
+    }
+
+    /// Set the maximum total size of album art kept in memory, in bytes.
+    ///
+    /// Which songs and directories album art belongs to is only remembered while the album art is
+    /// kept in memory. Additionally, up to 4096 songs and directories without album art are
+    /// remembered. A capacity of 0 disables caching album art data in memory.
+    pub fn memory_capacity(self, capacity: usize) -> Self {
+        {
+            let mut state = self.state.lock().unwrap();
+            let evicted = state.memory.set_capacity(capacity);
+            state.forget(&evicted);
+        }
+
+        self
+    }
+
+    /// Set the maximum total size of album art kept in the [disk cache][AlbumArtCache::disk_cache],
+    /// in bytes.
+    ///
+    /// Once the capacity is exceeded, the album art written least recently is removed. Defaults
+    /// to 256 MiB.
+    pub fn disk_capacity(mut self, capacity: u64) -> Self {
+        self.disk_capacity = capacity;
+        self
+    }
+
+    /// Additionally cache album art in the given directory.
+    ///
+    /// Since the cached album art depends on the music database of the server, `server` identifies
+    /// the server the client is connected to, for example by its address. Caches for different
+    /// servers may use the same directory, and are kept separate.
+    ///
+    /// The directory is created if it does not exist. Album art cached on disk persists across
+    /// restarts, until the cache is [invalidated][AlbumArtCache::invalidate]. When the disk cache
+    /// is first used, it is discarded if the [database was
+    /// updated][crate::responses::Stats::db_last_update] in the meantime. The size of the disk
+    /// cache is limited by its [capacity][AlbumArtCache::disk_capacity]. Errors when reading or
+    /// writing the disk cache are logged, but otherwise ignored.
+    pub fn disk_cache(mut self, path: impl Into<PathBuf>, server: &str) -> Self {
+        let path = path.into().join(Digest::of(server.as_bytes()).to_string());
+
+        self.disk = Some(Arc::new(Disk {
+            path,
+            state: tokio::sync::Mutex::new(DiskState {
+                validated: false,
+                size: 0,
+            }),
+        }));
+
+        self
+    }
+
+    /// Load album art for the given URI, using the cache if possible.
+    ///
+    /// This behaves the same as [`Client::album_art`], but returns the data as [`Bytes`] which can
+    /// be cheaply cloned.
+    ///
+    /// # Errors
+    ///
+    /// This returns errors in the same conditions as [`Client::album_art`]. Errors are not
+    /// cached.
+    #[tracing::instrument(skip(self))]
+    pub async fn album_art(
+        &self,
+        uri: &str,
+    ) -> Result<Option<(Bytes, Option<String>)>, CommandError> {
+        Ok(self.load(uri).await?.map(CachedArt::into_parts))
+    }
+
+    /// Load album art for the given URI, along with its digest.
+    pub(super) async fn load(&self, uri: &str) -> Result<Option<CachedArt>, CommandError> {
+        let song = Key::Song(uri);
+
+        if self.client.supports_embedded_album_art() {
+            match self.cached(song).await {
+                Lookup::Hit(art) => return Ok(Some(art)),
+                Lookup::None => {}
+                Lookup::Miss(generation) => {
+                    debug!("loading embedded album art");
+
+                    match self.client.load_album_art(uri, true).await {
+                        Ok(Some((data, mime))) => {
+                            let art = self.insert(generation, song, data.freeze(), mime).await;
+                            return Ok(Some(art));
+                        }
+                        Ok(None) => self.insert_none(generation, song),
+                        // The server version may be unknown
+                        Err(e) if e.error_code() == Some(ErrorCode::Unknown) => {
+                            debug!("readpicture command unsupported, falling back");
+                            self.insert_none(generation, song);
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+        }
+
+        let directory = Key::Directory(uri.rsplit_once('/').map_or("", |(dir, _)| dir));
+
+        match self.cached(directory).await {
+            Lookup::Hit(art) => Ok(Some(art)),
+            Lookup::None => Ok(None),
+            Lookup::Miss(generation) => {
+                debug!("loading separate album art");
+
+                match self.client.load_album_art(uri, false).await? {
+                    Some((data, mime)) => {
+                        let art = self
+                            .insert(generation, directory, data.freeze(), mime)
+                            .await;
+                        Ok(Some(art))
+                    }
+                    None => {
+                        self.insert_none(generation, directory);
+                        Ok(None)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Invalidate the cache if the given subsystem is the [database][Subsystem::Database].
+    ///
+    /// Call this with the subsystem of every [`SubsystemChange`] event to keep the cache up to
+    /// date.
+    ///
+    /// [`SubsystemChange`]: super::ConnectionEvent::SubsystemChange
+    pub async fn subsystem_changed(&self, subsystem: &Subsystem) {
+        if matches!(subsystem, Subsystem::Database) {
+            self.invalidate().await;
+        }
+    }
+
+    /// Remove all entries from the cache, including those on disk.
+    pub async fn invalidate(&self) {
+        debug!("invalidating album art cache");
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.generation += 1;
+            state.songs.clear();
+            state.directories.clear();
+            state.missing.clear();
+            state.memory.clear();
+        }
+
+        if let Some(disk) = &self.disk {
+            let mut state = disk.state.lock().await;
+            state.validated = false;
+
+            if let Err(e) = disk.clear().await {
+                warn!(error = ?e, "failed to clear disk cache");
+            }
+        }
+    }
+
+    /// Get the disk cache, if enabled.
+    ///
+    /// On first use, the disk cache is validated against the database of the server.
+    async fn disk(&self) -> Option<&Disk> {
+        let disk = self.disk.as_ref()?;
+        let mut state = disk.state.lock().await;
+
+        if !state.validated {
+            let stats = match self.client.command(cmds::Stats).await {
+                Ok(stats) => stats,
+                Err(e) => {
+                    warn!(error = ?e, "failed to query database update time");
+                    return None;
+                }
+            };
+
+            match disk.validate(stats.db_last_update).await {
+                Ok(size) => state.size = size,
+                Err(e) => {
+                    warn!(error = ?e, "failed to validate disk cache");
+                    return None;
+                }
+            }
+
+            state.validated = true;
+        }
+
+        Some(disk)
+    }
+
+    /// Look up the given key in memory, and then on disk.
+    async fn cached(&self, key: Key<'_>) -> Lookup {
+        let (generation, digest) = {
+            let mut state = self.state.lock().unwrap();
+
+            let digest = match state.index(key.kind()).get(key.name()) {
+                Some(Some(digest)) => Some(*digest),
+                Some(None) => return Lookup::None,
+                None => None,
+            };
+
+            if let Some(art) = digest.and_then(|digest| state.memory.get(digest)) {
+                trace!(?key, "memory cache hit");
+                return Lookup::Hit(art);
+            }
+
+            (state.generation, digest)
+        };
+
+        let Some(disk) = self.disk().await else {
+            return Lookup::Miss(generation);
+        };
+
+        let digest = match digest {
+            Some(digest) => digest,
+            None => match read_index(&disk.path, key).await {
+                Some(digest) => digest,
+                None => return Lookup::Miss(generation),
+            },
+        };
+
+        let Some(art) = read_art(&disk.path, digest).await else {
+            // The album art may have been removed to stay within the capacity
+            let _ = tokio::fs::remove_file(disk.path.join("index").join(key.file_name())).await;
+            return Lookup::Miss(generation);
+        };
+
+        trace!(?key, "disk cache hit");
+
+        let mut state = self.state.lock().unwrap();
+        if state.generation == generation {
+            state.insert(key, art.clone());
+        }
+
+        Lookup::Hit(art)
+    }
+
+    /// Insert album art, unless the cache was invalidated since the lookup.
+    async fn insert(
+        &self,
+        generation: u64,
+        key: Key<'_>,
+        data: Bytes,
+        mime: Option<String>,
+    ) -> CachedArt {
+        let art = CachedArt {
+            digest: Digest::of(&data),
+            data,
+            mime,
+        };
+
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.generation != generation {
+                return art;
+            }
+
+            state.insert(key, art.clone());
+        }
+
+        if let Some(disk) = self.disk().await {
+            match write_disk(&disk.path, key, &art).await {
+                Ok(0) => {}
+                Ok(written) => disk.added(written, self.disk_capacity).await,
+                Err(e) => warn!(error = ?e, "failed to write disk cache"),
+            }
+        }
+
+        art
+    }
+
+    /// Remember that there is no album art for the given key.
+    fn insert_none(&self, generation: u64, key: Key<'_>) {
+        let mut state = self.state.lock().unwrap();
+        if state.generation == generation {
+            state.insert_none(key);
+        }
+    }
+}
+
+impl fmt::Debug for AlbumArtCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+
+        f.debug_struct("AlbumArtCache")
+            .field("disk", &self.disk.as_ref().map(|disk| &disk.path))
+            .field("disk_capacity", &self.disk_capacity)
+            .field("songs", &state.songs.len())
+            .field("directories", &state.directories.len())
+            .field("memory", &state.memory)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The shared, mutable state of a cache.
+struct State {
+    /// Incremented on invalidation, to avoid inserting stale entries loaded concurrently.
+    generation: u64,
+    /// Digests of the embedded album art of songs, or `None` if they have none.
+    songs: HashMap<String, Option<Digest>>,
+    /// Digests of the album art files in directories, or `None` if they have none.
+    directories: HashMap<String, Option<Digest>>,
+    /// Songs and directories without album art, from least to most recently inserted.
+    missing: VecDeque<(Kind, String)>,
+    memory: MemoryCache,
+}
+
+impl State {
+    fn index(&mut self, kind: Kind) -> &mut HashMap<String, Option<Digest>> {
+        match kind {
+            Kind::Song => &mut self.songs,
+            Kind::Directory => &mut self.directories,
+        }
+    }
+
+    fn insert(&mut self, key: Key<'_>, art: CachedArt) {
+        self.index(key.kind())
+            .insert(key.name().to_owned(), Some(art.digest));
+
+        let evicted = self.memory.insert(art.digest, art);
+        self.forget(&evicted);
+    }
+
+    fn insert_none(&mut self, key: Key<'_>) {
+        self.index(key.kind()).insert(key.name().to_owned(), None);
+        self.missing.push_back((key.kind(), key.name().to_owned()));
+
+        while self.missing.len() > MAX_MISSING {
+            let Some((kind, name)) = self.missing.pop_front() else {
+                break;
+            };
+
+            let index = self.index(kind);
+            if index.get(&name) == Some(&None) {
+                index.remove(&name);
+            }
+        }
+    }
+
+    /// Remove the index entries of album art evicted from memory.
+    fn forget(&mut self, evicted: &[Digest]) {
+        if evicted.is_empty() {
+            return;
+        }
+
+        for index in [&mut self.songs, &mut self.directories] {
+            index.retain(|_, digest| digest.is_none_or(|digest| !evicted.contains(&digest)));
+        }
+    }
+}
+
+/// What album art is cached under.
+#[derive(Clone, Copy, Debug)]
+enum Key<'a> {
+    /// Embedded album art of the song with the given URI.
+    Song(&'a str),
+    /// Separate album art file in the given directory.
+    Directory(&'a str),
+}
+
+/// The kind of a [`Key`].
+#[derive(Clone, Copy, Debug)]
+enum Kind {
+    Song,
+    Directory,
+}
+
+impl Key<'_> {
+    fn kind(&self) -> Kind {
+        match self {
+            Key::Song(_) => Kind::Song,
+            Key::Directory(_) => Kind::Directory,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Key::Song(uri) => uri,
+            Key::Directory(dir) => dir,
+        }
+    }
+
+    /// Name of the file containing the digest of the album art for this key on disk.
+    fn file_name(&self) -> String {
+        let (prefix, name) = match self {
+            Key::Song(uri) => ("song", uri),
+            Key::Directory(dir) => ("directory", dir),
+        };
+
+        format!("{prefix}-{}", Digest::of(name.as_bytes()))
+    }
+}
+
+enum Lookup {
+    Hit(CachedArt),
+    /// There is no album art for the key.
+    None,
+    /// The key is not cached, with the generation at the time of the lookup.
+    Miss(u64),
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct CachedArt {
+    pub(super) data: Bytes,
+    pub(super) mime: Option<String>,
+    pub(super) digest: Digest,
+}
+
+impl CachedArt {
+    fn into_parts(self) -> (Bytes, Option<String>) {
+        (self.data, self.mime)
+    }
+}
+
+/// In-memory LRU cache of album art by digest.
+pub(super) struct MemoryCache {
+    entries: HashMap<Digest, CachedArt>,
+    /// Digests of the entries, from least to most recently used.
+    order: VecDeque<Digest>,
+    size: usize,
+    capacity: usize,
+}
+
+impl MemoryCache {
+    pub(super) fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            size: 0,
+            capacity,
+        }
+    }
+
+    pub(super) fn get(&mut self, digest: Digest) -> Option<CachedArt> {
+        let art = self.entries.get(&digest)?.clone();
+        self.touch(digest);
+        Some(art)
+    }
+
+    /// Insert an entry, returning the digests of the entries which were evicted.
+    ///
+    /// Entries larger than the capacity are not inserted, and are returned as evicted.
+    pub(super) fn insert(&mut self, digest: Digest, art: CachedArt) -> Vec<Digest> {
+        if art.data.len() > self.capacity {
+            return vec![digest];
+        }
+
+        if let Some(previous) = self.entries.insert(digest, art) {
+            self.size -= previous.data.len();
+            self.touch(digest);
+        } else {
+            self.order.push_back(digest);
+        }
+
+        self.size += self.entries[&digest].data.len();
+        self.evict()
+    }
+
+    /// Set the capacity, returning the digests of the entries which were evicted.
+    pub(super) fn set_capacity(&mut self, capacity: usize) -> Vec<Digest> {
+        self.capacity = capacity;
+        self.evict()
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.size = 0;
+    }
+
+    fn touch(&mut self, digest: Digest) {
+        if let Some(index) = self.order.iter().position(|d| *d == digest) {
+            self.order.remove(index);
+            self.order.push_back(digest);
+        }
+    }
+
+    fn evict(&mut self) -> Vec<Digest> {
+        let mut evicted = Vec::new();
+
+        while self.size > self.capacity {
+            let Some(digest) = self.order.pop_front() else {
+                break;
+            };
+
+            if let Some(art) = self.entries.remove(&digest) {
+                self.size -= art.data.len();
+                evicted.push(digest);
+            }
+        }
+
+        evicted
+    }
+}
+
+impl fmt::Debug for MemoryCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryCache")
+            .field("entries", &self.entries.len())
+            .field("size", &self.size)
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+/// The disk cache of a single server.
+struct Disk {
+    path: PathBuf,
+    state: tokio::sync::Mutex<DiskState>,
+}
+
+struct DiskState {
+    /// Whether the disk cache was validated since it was last invalidated.
+    validated: bool,
+    /// Total size of the album art on disk, in bytes.
+    size: u64,
+}
+
+impl Disk {
+    /// Clear the disk cache if the database was updated since it was last used, returning the
+    /// total size of the album art on disk.
+    async fn validate(&self, db_update: u64) -> io::Result<u64> {
+        let path = self.path.join("db_update");
+        let db_update = db_update.to_string();
+
+        match tokio::fs::read_to_string(&path).await {
+            Ok(stored) if stored == db_update => {
+                let files = self.art_files().await?;
+                return Ok(files.iter().map(|(_, size, _)| size).sum());
+            }
+            Ok(_) => debug!("database was updated, clearing disk cache"),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        self.clear().await?;
+        tokio::fs::create_dir_all(&self.path).await?;
+        write_atomic(&path, db_update.as_bytes()).await?;
+        Ok(0)
+    }
+
+    /// Account for album art written to disk, removing the album art written least recently if
+    /// the capacity is exceeded.
+    async fn added(&self, written: u64, capacity: u64) {
+        let mut state = self.state.lock().await;
+        state.size += written;
+
+        if state.size <= capacity {
+            return;
+        }
+
+        let mut files = match self.art_files().await {
+            Ok(files) => files,
+            Err(e) => {
+                warn!(error = ?e, "failed to list disk cache");
+                return;
+            }
+        };
+
+        files.sort_by_key(|(_, _, modified)| *modified);
+        state.size = files.iter().map(|(_, size, _)| size).sum();
+
+        for (path, size, _) in files {
+            if state.size <= capacity {
+                break;
+            }
+
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => state.size -= size,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => state.size -= size,
+                Err(e) => warn!(error = ?e, ?path, "failed to remove album art from disk cache"),
+            }
+        }
+
+        debug!(size = state.size, "removed album art from disk cache");
+    }
+
+    /// List the album art files with their sizes and modification times.
+    async fn art_files(&self) -> io::Result<Vec<(PathBuf, u64, SystemTime)>> {
+        let mut entries = match tokio::fs::read_dir(self.path.join("art")).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut files = Vec::new();
+
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata = entry.metadata().await?;
+
+            if metadata.is_file() {
+                files.push((entry.path(), metadata.len(), metadata.modified()?));
+            }
+        }
+
+        Ok(files)
+    }
+
+    async fn clear(&self) -> io::Result<()> {
+        for dir in [self.path.join("index"), self.path.join("art")] {
+            match tokio::fs::remove_dir_all(&dir).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+async fn read_index(disk: &Path, key: Key<'_>) -> Option<Digest> {
+    let raw = tokio::fs::read_to_string(disk.join("index").join(key.file_name()))
+        .await
+        .ok()?;
+
+    Digest::parse(raw.trim())
+}
+
+/// Read album art stored on disk, which consists of the MIME type on the first line followed by
+/// the data.
+async fn read_art(disk: &Path, digest: Digest) -> Option<CachedArt> {
+    let raw = tokio::fs::read(disk.join("art").join(digest.to_string()))
+        .await
+        .ok()?;
+
+    let newline = raw.iter().position(|b| *b == b'\n')?;
+    let mime = std::str::from_utf8(&raw[..newline]).ok()?;
+    let mime = (!mime.is_empty()).then(|| mime.to_owned());
+
+    Some(CachedArt {
+        data: Bytes::from(raw).slice(newline + 1..),
+        mime,
+        digest,
+    })
+}
+
+/// Write album art and its index entry to disk, returning the number of bytes of album art
+/// written, which is 0 if the album art was already stored.
+async fn write_disk(disk: &Path, key: Key<'_>, art: &CachedArt) -> io::Result<u64> {
+    let art_dir = disk.join("art");
+    let index_dir = disk.join("index");
+    tokio::fs::create_dir_all(&art_dir).await?;
+    tokio::fs::create_dir_all(&index_dir).await?;
+
+    let path = art_dir.join(art.digest.to_string());
+    let mut written = 0;
+
+    // Write the data before the index, so that the index never refers to missing data
+    if !tokio::fs::try_exists(&path).await? {
+        let mime = art.mime.as_deref().unwrap_or_default();
+        let mut contents = Vec::with_capacity(mime.len() + 1 + art.data.len());
+        contents.extend_from_slice(mime.as_bytes());
+        contents.push(b'\n');
+        contents.extend_from_slice(&art.data);
+
+        write_atomic(&path, &contents).await?;
+        written = contents.len() as u64;
+    }
+
+    write_atomic(
+        &index_dir.join(key.file_name()),
+        art.digest.to_string().as_bytes(),
+    )
+    .await?;
+
+    Ok(written)
+}
+
+/// Write a file by writing a temporary file and renaming it, so that the file is never observed
+/// partially written, even by other processes sharing the directory.
+pub(super) async fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(
+        ".{}-{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = match tokio::fs::write(&temp, contents).await {
+        Ok(()) => tokio::fs::rename(&temp, path).await,
+        Err(e) => Err(e),
+    };
+
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&temp).await;
+    }
+
+    result
+}
+
+/// SHA-256 digest, used to identify cached data in memory and name it on disk.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) struct Digest([u8; 32]);
+
+impl Digest {
+    pub(super) fn of(data: &[u8]) -> Self {
+        Self(Sha256::digest(data).into())
+    }
+
+    /// Parse a digest from its hex representation.
+    fn parse(hex: &str) -> Option<Self> {
+        if hex.len() != 64 || !hex.is_ascii() {
+            return None;
+        }
+
+        let mut digest = [0; 32];
+
+        for (i, byte) in digest.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+        }
+
+        Some(Self(digest))
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+impl fmt::Debug for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Digest({self})")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn art(data: &'static [u8]) -> CachedArt {
+        CachedArt {
+            data: Bytes::from_static(data),
+            mime: None,
+            digest: Digest::of(data),
+        }
+    }
+
+    fn digest(n: u8) -> Digest {
+        Digest([n; 32])
+    }
+
+    #[test]
+    fn memory_cache_lru() {
+        let mut cache = MemoryCache::new(6);
+
+        cache.insert(digest(1), art(b"foo"));
+        cache.insert(digest(2), art(b"bar"));
+        assert!(cache.get(digest(1)).is_some());
+
+        // Evicts 2, which was used least recently
+        assert_eq!(cache.insert(digest(3), art(b"baz")), [digest(2)]);
+        assert!(cache.get(digest(2)).is_none());
+        assert!(cache.get(digest(1)).is_some());
+        assert!(cache.get(digest(3)).is_some());
+        assert_eq!(cache.size, 6);
+
+        // Larger than the capacity
+        assert_eq!(cache.insert(digest(4), art(b"abcdefg")), [digest(4)]);
+        assert!(cache.get(digest(4)).is_none());
+        assert_eq!(cache.size, 6);
+
+        assert_eq!(cache.set_capacity(3), [digest(1)]);
+        assert!(cache.get(digest(1)).is_none());
+        assert!(cache.get(digest(3)).is_some());
+    }
+
+    #[test]
+    fn state_bounded() {
+        let mut state = State {
+            generation: 0,
+            songs: HashMap::new(),
+            directories: HashMap::new(),
+            missing: VecDeque::new(),
+            memory: MemoryCache::new(6),
+        };
+
+        state.insert(Key::Song("foo"), art(b"foo"));
+        state.insert(Key::Directory("bar"), art(b"foo"));
+        state.insert(Key::Song("bar"), art(b"bar"));
+        assert_eq!(state.songs.len(), 2);
+
+        // Evicting the album art from memory also forgets where it belongs
+        state.insert(Key::Song("baz"), art(b"baz"));
+        assert_eq!(state.songs.len(), 2);
+        assert!(!state.songs.contains_key("foo"));
+        assert!(state.directories.is_empty());
+
+        for i in 0..=MAX_MISSING {
+            state.insert_none(Key::Directory(&i.to_string()));
+        }
+
+        assert_eq!(state.directories.len(), MAX_MISSING);
+        assert!(!state.directories.contains_key("0"));
+    }
+
+    #[test]
+    fn sha256_digest() {
+        let empty = Digest::of(b"");
+        let hex = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+        assert_eq!(empty.to_string(), hex);
+        assert_eq!(Digest::parse(hex), Some(empty));
+        assert_eq!(Digest::parse(&hex[1..]), None);
+        assert_eq!(Digest::parse(&hex.replace('e', "g")), None);
+    }
+}
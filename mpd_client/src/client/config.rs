@@ -0,0 +1,191 @@
+use std::{fmt, time::Duration};
+
+use super::connection::Options;
+use crate::tag::Tag;
+
+/// Default time to wait for another command to send before starting to idle again.
+const DEFAULT_IDLE_RESUME_DELAY: Duration = Duration::from_millis(100);
+
+/// Default interval after which an unused command connection in split mode sends a `ping`.
+const DEFAULT_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Configuration for connecting a [`Client`][super::Client].
+///
+/// Used with [`Client::connect_with_config`][super::Client::connect_with_config]. The default
+/// configuration behaves the same as [`Client::connect`][super::Client::connect].
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use mpd_client::{client::ClientConfig, tag::Tag};
+///
+/// let config = ClientConfig::new()
+///     .idle_resume_delay(Duration::from_millis(20))
+///     .command_queue_size(64)
+///     .tag_types([Tag::Artist, Tag::Album, Tag::Title]);
+/// ```
+#[derive(Clone, Default)]
+pub struct ClientConfig {
+    pub(super) password: Option<String>,
+    pub(super) idle_resume_delay: Option<Duration>,
+    pub(super) keepalive_interval: Option<Duration>,
+    pub(super) buffer_capacity: Option<usize>,
+    pub(super) command_queue_size: Option<usize>,
+    pub(super) max_in_flight: Option<usize>,
+    pub(super) batch_commands: bool,
+    pub(super) binary_limit: Option<usize>,
+    pub(super) tag_types: Option<Vec<Tag>>,
+}
+
+impl ClientConfig {
+    /// Create the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the password to send after connecting.
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Set the time to wait for another command after receiving the response to a command, before
+    /// resuming listening for state changes.
+    ///
+    /// Shorter delays reduce the latency of state change notifications, but increase the number
+    /// of round-trips when sending commands in quick succession. Defaults to 100 milliseconds.
+    pub fn idle_resume_delay(mut self, delay: Duration) -> Self {
+        self.idle_resume_delay = Some(delay);
+        self
+    }
+
+    /// Set the interval after which the command connection sends a `ping` if it was not used, to
+    /// prevent MPD from closing it due to its `connection_timeout`.
+    ///
+    /// This only has an effect when using a [split connection][super::Client::connect_split],
+    /// since a single connection is kept open by listening for state changes. Defaults to 30
+    /// seconds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    pub fn keepalive_interval(mut self, interval: Duration) -> Self {
+        assert!(!interval.is_zero(), "keepalive interval must not be zero");
+        self.keepalive_interval = Some(interval);
+        self
+    }
+
+    /// Set the initial capacity of the receive buffer, in bytes.
+    ///
+    /// The buffer grows as needed, but a larger initial capacity avoids reallocations when
+    /// receiving large responses.
+    pub fn receive_buffer_capacity(mut self, capacity: usize) -> Self {
+        self.buffer_capacity = Some(capacity);
+        self
+    }
+
+    /// Limit the number of commands waiting to be sent.
+    ///
+    /// When the queue is full, sending commands waits until there is space again. By default, the
+    /// queue is unbounded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn command_queue_size(mut self, size: usize) -> Self {
+        assert!(size > 0, "command queue size must be greater than 0");
+        self.command_queue_size = Some(size);
+        self
+    }
+
+    /// Set the maximum number of commands (or command lists) which may be sent to the server
+    /// before their responses were received.
+    ///
+    /// Values greater than 1 enable pipelining, which allows concurrently issued commands to be
+    /// sent back to back instead of waiting for a full round-trip each. The responses are still
+    /// returned to the respective callers. Defaults to 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is 0.
+    pub fn max_in_flight(mut self, max: usize) -> Self {
+        assert!(
+            max > 0,
+            "maximum number of commands in flight must be greater than 0"
+        );
+        self.max_in_flight = Some(max);
+        self
+    }
+
+    /// Automatically combine commands which are issued concurrently into command lists.
+    ///
+    /// When enabled, all commands (and command lists) waiting to be sent are combined into a
+    /// single command list, which reduces the number of round-trips. Each caller still receives
+    /// the response to its own commands. If a command in such a batch returns an error, the
+    /// commands after it are not executed, and their callers receive a
+    /// [`BatchAborted`][super::CommandError::BatchAborted] error. Disabled by default.
+    ///
+    /// A batch contains at most 128 commands, and stays within the [default maximum
+    /// size][super::DEFAULT_MAX_COMMAND_LIST_SIZE] of command lists. Command lists which don't fit
+    /// are sent with the next batch.
+    pub fn batch_commands(mut self, enabled: bool) -> Self {
+        self.batch_commands = enabled;
+        self
+    }
+
+    /// Set the [binary limit][crate::commands::SetBinaryLimit] after connecting.
+    ///
+    /// This is ignored if the server does not support the `binarylimit` command.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is less than 64, which MPD rejects.
+    pub fn binary_limit(mut self, limit: usize) -> Self {
+        assert!(limit >= 64, "binary limit must be at least 64 bytes");
+        self.binary_limit = Some(limit);
+        self
+    }
+
+    /// Set the tags which the server includes in responses.
+    ///
+    /// After connecting, all other tags are [disabled][crate::commands::TagTypes], which reduces
+    /// the size of responses containing songs. The tags the server enabled are remembered, see
+    /// [`Client::tag_types`][super::Client::tag_types].
+    pub fn tag_types<I>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = Tag>,
+    {
+        self.tag_types = Some(tags.into_iter().collect());
+        self
+    }
+}
+
+impl ClientConfig {
+    pub(super) fn run_loop_options(&self) -> Options {
+        Options {
+            idle_resume_delay: self.idle_resume_delay.unwrap_or(DEFAULT_IDLE_RESUME_DELAY),
+            keepalive_interval: self
+                .keepalive_interval
+                .unwrap_or(DEFAULT_KEEPALIVE_INTERVAL),
+            max_in_flight: self.max_in_flight.unwrap_or(1),
+            batch_commands: self.batch_commands,
+        }
+    }
+}
+
+impl fmt::Debug for ClientConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // avoid printing the password
+        f.debug_struct("ClientConfig")
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("idle_resume_delay", &self.idle_resume_delay)
+            .field("keepalive_interval", &self.keepalive_interval)
+            .field("buffer_capacity", &self.buffer_capacity)
+            .field("command_queue_size", &self.command_queue_size)
+            .field("max_in_flight", &self.max_in_flight)
+            .field("batch_commands", &self.batch_commands)
+            .field("binary_limit", &self.binary_limit)
+            .field("tag_types", &self.tag_types)
+            .finish()
+    }
+}
@@ -1,29 +1,53 @@
-use std::{fmt, time::Duration};
+use std::{collections::VecDeque, fmt, io, mem, time::Duration};
 
 use mpd_protocol::{
     AsyncConnection, MpdProtocolError,
     command::{Command as RawCommand, CommandList as RawCommandList},
-    response::Response,
+    response::{Frame, Response},
 };
 use tokio::{
     io::{AsyncRead, AsyncWrite},
-    sync::mpsc::{UnboundedReceiver, UnboundedSender},
+    sync::{
+        mpsc::{self, UnboundedSender},
+        oneshot, watch,
+    },
     time::timeout,
 };
-use tracing::{Instrument, Level, debug, error, span, trace};
+use tracing::{Instrument, Level, debug, error, span, trace, warn};
 
-use crate::client::{CommandResponder, ConnectionError, ConnectionEvent, Subsystem};
+use crate::client::{
+    CommandResponder, ConnectionError, RawEvent, Subsystem,
+    batch::{self, CommandQueue, Responder},
+};
 
 struct State<C> {
     loop_state: LoopState,
     connection: AsyncConnection<C>,
-    commands: UnboundedReceiver<(RawCommandList, CommandResponder)>,
-    events: UnboundedSender<ConnectionEvent>,
+    commands: CommandQueue,
+    events: UnboundedSender<RawEvent>,
+    subsystems: watch::Receiver<Vec<Subsystem>>,
+    options: Options,
+}
+
+/// Tuning options for the run loops.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Options {
+    /// Time to wait for another command to send before starting to idle again.
+    pub(super) idle_resume_delay: Duration,
+    /// Interval after which an otherwise unused command connection in split mode sends a `ping`,
+    /// to prevent MPD from closing it due to its `connection_timeout`.
+    pub(super) keepalive_interval: Duration,
+    /// Maximum number of command lists sent without having received their responses.
+    pub(super) max_in_flight: usize,
+    /// Whether to combine immediately available command lists into a single one.
+    pub(super) batch_commands: bool,
 }
 
 enum LoopState {
     Idling,
-    WaitingForCommandReply(CommandResponder),
+    /// Waiting for the responses to one or more commands, in the order they were sent. Never
+    /// empty.
+    WaitingForCommandReply(VecDeque<Responder>),
 }
 
 impl fmt::Debug for LoopState {
@@ -31,13 +55,25 @@ impl fmt::Debug for LoopState {
         // avoid Debug-printing the noisy internals of the contained channel type
         match self {
             LoopState::Idling => write!(f, "Idling"),
-            LoopState::WaitingForCommandReply(_) => write!(f, "WaitingForCommandReply"),
+            LoopState::WaitingForCommandReply(responders) => {
+                write!(f, "WaitingForCommandReply({})", responders.len())
+            }
         }
     }
 }
 
-fn idle() -> RawCommand {
-    RawCommand::new("idle")
+/// Create an `idle` command for the currently configured subsystems, marking the configuration as
+/// seen.
+fn idle(subsystems: &mut watch::Receiver<Vec<Subsystem>>) -> RawCommand {
+    let mut command = RawCommand::new("idle");
+
+    for subsystem in subsystems.borrow_and_update().iter() {
+        if let Err(e) = command.add_argument(subsystem.as_str()) {
+            warn!(?subsystem, error = %e, "ignoring invalid idle subsystem");
+        }
+    }
+
+    command
 }
 
 fn cancel_idle() -> RawCommand {
@@ -46,23 +82,27 @@ fn cancel_idle() -> RawCommand {
 
 pub(super) async fn run_loop<C>(
     mut connection: AsyncConnection<C>,
-    commands: UnboundedReceiver<(RawCommandList, CommandResponder)>,
-    events: UnboundedSender<ConnectionEvent>,
+    commands: mpsc::Receiver<(RawCommandList, CommandResponder)>,
+    events: UnboundedSender<RawEvent>,
+    mut subsystems: watch::Receiver<Vec<Subsystem>>,
+    options: Options,
 ) where
     C: AsyncRead + AsyncWrite + Unpin,
 {
     trace!("sending initial idle command");
-    if let Err(e) = connection.send(idle()).await {
+    if let Err(e) = connection.send(idle(&mut subsystems)).await {
         error!(error = ?e, "failed to send initial idle command");
-        let _ = events.send(ConnectionEvent::ConnectionClosed(e.into()));
+        let _ = events.send(RawEvent::Closed(e.into()));
         return;
     }
 
     let mut state = State {
         loop_state: LoopState::Idling,
         connection,
-        commands,
+        commands: CommandQueue::new(commands),
         events,
+        subsystems,
+        options,
     };
 
     trace!("entering run loop");
@@ -79,14 +119,228 @@ pub(super) async fn run_loop<C>(
     trace!("exited run_loop");
 }
 
-/// Time to wait for another command to send before starting the idle loop.
-const NEXT_COMMAND_IDLE_TIMEOUT: Duration = Duration::from_millis(100);
+/// Links the run loops of the two connections in split mode, so that each of them stops when the
+/// other one does.
+pub(super) struct Link {
+    /// Dropped when the owning loop exits, which completes `other` of the other loop.
+    _alive: oneshot::Sender<()>,
+    /// Completes once the other loop exited.
+    other: oneshot::Receiver<()>,
+}
+
+impl Link {
+    pub(super) fn new() -> (Link, Link) {
+        let (a_alive, a_exited) = oneshot::channel();
+        let (b_alive, b_exited) = oneshot::channel();
+
+        let a = Link {
+            _alive: a_alive,
+            other: b_exited,
+        };
+        let b = Link {
+            _alive: b_alive,
+            other: a_exited,
+        };
+
+        (a, b)
+    }
+}
+
+/// Run loop for the command connection in split mode, which never idles.
+///
+/// If the connection is closed or an error occurs, this emits a [`RawEvent::Closed`] event, which
+/// also stops the idle loop through the `link`.
+pub(super) async fn run_command_loop<C>(
+    mut connection: AsyncConnection<C>,
+    commands: mpsc::Receiver<(RawCommandList, CommandResponder)>,
+    events: UnboundedSender<RawEvent>,
+    mut link: Link,
+    options: Options,
+) where
+    C: AsyncRead + AsyncWrite + Unpin,
+{
+    trace!("entering command loop");
+
+    let mut commands = CommandQueue::new(commands);
+    let mut responders: VecDeque<Responder> = VecDeque::new();
+
+    let error = loop {
+        let can_pipeline = responders.len() < options.max_in_flight;
+
+        let command = tokio::select! {
+            biased;
+            _ = &mut link.other => {
+                debug!("idle connection closed, closing command connection");
+                break None;
+            }
+            // Also receive while no commands are in flight, to notice the connection closing
+            response = connection.receive() => {
+                let response = match response {
+                    Ok(Some(response)) => response,
+                    Ok(None) => {
+                        error!("command connection closed");
+                        break Some(unexpected_eof().into());
+                    }
+                    Err(e) => {
+                        error!(error = ?e, "failed to receive command response");
+
+                        if let Some(responder) = responders.pop_front() {
+                            responder.send(Err(duplicate_error(&e).into()));
+                        }
+
+                        break Some(e.into());
+                    }
+                };
+
+                let Some(responder) = responders.pop_front() else {
+                    error!("received response without a command in flight");
+                    break Some(ConnectionError::InvalidResponse);
+                };
+
+                trace!("response to command received");
+                responder.send(Ok(response));
+                continue;
+            }
+            command = timeout(options.keepalive_interval, commands.recv()), if can_pipeline => {
+                match command {
+                    Ok(command) => command,
+                    Err(_) if responders.is_empty() => {
+                        trace!("sending keepalive");
+
+                        if let Err(e) = keepalive(&mut connection).await {
+                            error!(error = ?e, "keepalive failed");
+                            break Some(e.into());
+                        }
+
+                        continue;
+                    }
+                    // No keepalive necessary while waiting for responses
+                    Err(_) => continue,
+                }
+            }
+        };
+
+        // All clients were dropped
+        let Some(command) = command else {
+            break None;
+        };
+
+        let (command, responder) = batch::collect(command, &mut commands, options.batch_commands);
+
+        trace!(?command, in_flight = responders.len(), "command received");
+
+        if let Err(e) = connection.send_list(command).await {
+            error!(error = ?e, "failed to send command");
+            responder.send(Err(duplicate_error(&e).into()));
+            break Some(e.into());
+        }
+
+        responders.push_back(responder);
+    };
+
+    if let Some(error) = error {
+        let _ = events.send(RawEvent::Closed(error));
+    }
+
+    trace!("exited command loop");
+}
+
+async fn keepalive<C>(connection: &mut AsyncConnection<C>) -> Result<(), MpdProtocolError>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+{
+    connection.send(RawCommand::new("ping")).await?;
+
+    match connection.receive().await? {
+        Some(_) => Ok(()),
+        None => Err(unexpected_eof()),
+    }
+}
+
+fn unexpected_eof() -> MpdProtocolError {
+    MpdProtocolError::Io(io::ErrorKind::UnexpectedEof.into())
+}
+
+/// Create a copy of the given error, which can't be cloned, to report it to both a caller and the
+/// connection events.
+fn duplicate_error(error: &MpdProtocolError) -> MpdProtocolError {
+    match error {
+        MpdProtocolError::Io(e) => MpdProtocolError::Io(io::Error::new(e.kind(), e.to_string())),
+        MpdProtocolError::InvalidMessage => MpdProtocolError::InvalidMessage,
+    }
+}
+
+/// Run loop for the idle connection in split mode, which never sends any other commands.
+///
+/// This exits when all clients are dropped, which is detected by the subsystems channel closing,
+/// or when the command loop exited, which is detected through the `link`.
+pub(super) async fn run_idle_loop<C>(
+    mut connection: AsyncConnection<C>,
+    events: UnboundedSender<RawEvent>,
+    mut subsystems: watch::Receiver<Vec<Subsystem>>,
+    mut link: Link,
+) where
+    C: AsyncRead + AsyncWrite + Unpin,
+{
+    trace!("entering idle loop");
+
+    loop {
+        if let Err(e) = connection.send(idle(&mut subsystems)).await {
+            error!(error = ?e, "failed to send idle command");
+            let _ = events.send(RawEvent::Closed(e.into()));
+            break;
+        }
+
+        let response = tokio::select! {
+            response = connection.receive() => response,
+            _ = &mut link.other => {
+                // The command loop already reported why it exited, if necessary
+                debug!("command connection closed, closing idle connection");
+                break;
+            }
+            changed = subsystems.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+
+                trace!("idle subsystems changed, restarting idle");
+
+                if let Err(e) = connection.send(cancel_idle()).await {
+                    error!(error = ?e, "failed to cancel idle after subsystems change");
+                    let _ = events.send(RawEvent::Closed(e.into()));
+                    break;
+                }
+
+                connection.receive().await
+            }
+        };
+
+        match response {
+            Ok(Some(res)) => match res.into_single_frame() {
+                Ok(f) => emit_changes(&events, &f),
+                Err(e) => {
+                    error!(code = e.code, message = e.message, "idle returned an error");
+                    let _ = events.send(RawEvent::Closed(ConnectionError::InvalidResponse));
+                    break;
+                }
+            },
+            Ok(None) => break, // The connection was closed
+            Err(e) => {
+                error!(error = ?e, "state change error");
+                let _ = events.send(RawEvent::Closed(e.into()));
+                break;
+            }
+        }
+    }
+
+    trace!("exited idle loop");
+}
 
 async fn run_loop_iteration<C>(mut state: State<C>) -> Result<State<C>, ()>
 where
     C: AsyncRead + AsyncWrite + Unpin,
 {
-    match state.loop_state {
+    match mem::replace(&mut state.loop_state, LoopState::Idling) {
         LoopState::Idling => {
             // We are idling (the last command sent to the server was an IDLE).
 
@@ -99,44 +353,51 @@ where
                 command = state.commands.recv() => {
                     handle_command(&mut state, command).await?;
                 }
+                changed = state.subsystems.changed() => {
+                    // The sender is only dropped when all clients are dropped
+                    changed.map_err(|_| ())?;
+                    handle_subsystems_change(&mut state).await?;
+                }
             }
         }
-        LoopState::WaitingForCommandReply(responder) => {
-            // We're waiting for the response to the command associated with `responder`.
-
-            let response = state.connection.receive().await.transpose().ok_or(())?;
-            trace!("response to command received");
-
-            let _ = responder.send(response.map_err(Into::into));
-
-            let next_command = timeout(NEXT_COMMAND_IDLE_TIMEOUT, state.commands.recv());
-
-            // See if we can immediately send the next command
-            match next_command.await {
-                Ok(Some((command, responder))) => {
-                    trace!(?command, "next command immediately available");
-                    match state.connection.send_list(command).await {
-                        Ok(_) => state.loop_state = LoopState::WaitingForCommandReply(responder),
-                        Err(e) => {
-                            error!(error = ?e, "failed to send command");
-                            let _ = responder.send(Err(e.into()));
-                            return Err(());
-                        }
+        LoopState::WaitingForCommandReply(mut responders) => {
+            // We're waiting for the responses to the commands associated with `responders`. If
+            // there is room, further commands may be sent before the responses arrive.
+            let can_pipeline = responders.len() < state.options.max_in_flight;
+
+            tokio::select! {
+                biased;
+                response = state.connection.receive() => {
+                    let response = response.transpose().ok_or(())?;
+                    trace!("response to command received");
+
+                    let responder = responders
+                        .pop_front()
+                        .expect("no responder for command response");
+                    responder.send(response.map_err(Into::into));
+
+                    if responders.is_empty() {
+                        resume_idle(&mut state).await?;
+                    } else {
+                        state.loop_state = LoopState::WaitingForCommandReply(responders);
                     }
                 }
-                Ok(None) => return Err(()),
-                Err(_) => {
-                    trace!("reached next command timeout, idling");
-
-                    // Start idling again
-                    state.loop_state = LoopState::Idling;
-                    if let Err(e) = state.connection.send(idle()).await {
-                        error!(error = ?e, "failed to start idling after receiving command response");
-                        let _ = state
-                            .events
-                            .send(ConnectionEvent::ConnectionClosed(e.into()));
+                command = state.commands.recv(), if can_pipeline => {
+                    let (command, responder) = batch::collect(
+                        command.ok_or(())?,
+                        &mut state.commands,
+                        state.options.batch_commands,
+                    );
+                    trace!(?command, in_flight = responders.len(), "pipelining command");
+
+                    if let Err(e) = state.connection.send_list(command).await {
+                        error!(error = ?e, "failed to send command");
+                        responder.send(Err(e.into()));
                         return Err(());
                     }
+
+                    responders.push_back(responder);
+                    state.loop_state = LoopState::WaitingForCommandReply(responders);
                 }
             }
         }
@@ -145,6 +406,49 @@ where
     Ok(state)
 }
 
+/// Called after all command responses were received: Waits briefly for another command to send,
+/// and starts idling again if there is none.
+async fn resume_idle<C>(state: &mut State<C>) -> Result<(), ()>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+{
+    let next_command = timeout(state.options.idle_resume_delay, state.commands.recv());
+
+    // See if we can immediately send the next command
+    match next_command.await {
+        Ok(Some(command)) => {
+            let (command, responder) =
+                batch::collect(command, &mut state.commands, state.options.batch_commands);
+            trace!(?command, "next command immediately available");
+            match state.connection.send_list(command).await {
+                Ok(_) => {
+                    state.loop_state =
+                        LoopState::WaitingForCommandReply(VecDeque::from([responder]))
+                }
+                Err(e) => {
+                    error!(error = ?e, "failed to send command");
+                    responder.send(Err(e.into()));
+                    return Err(());
+                }
+            }
+        }
+        Ok(None) => return Err(()),
+        Err(_) => {
+            trace!("reached next command timeout, idling");
+
+            // Start idling again
+            state.loop_state = LoopState::Idling;
+            if let Err(e) = state.connection.send(idle(&mut state.subsystems)).await {
+                error!(error = ?e, "failed to start idling after receiving command response");
+                let _ = state.events.send(RawEvent::Closed(e.into()));
+                return Err(());
+            }
+        }
+    }
+
+    Ok(())
+}
+
 async fn handle_command<C>(
     state: &mut State<C>,
     command: Option<(RawCommandList, CommandResponder)>,
@@ -152,13 +456,17 @@ async fn handle_command<C>(
 where
     C: AsyncRead + AsyncWrite + Unpin,
 {
-    let (command, responder) = command.ok_or(())?;
+    let (command, responder) = batch::collect(
+        command.ok_or(())?,
+        &mut state.commands,
+        state.options.batch_commands,
+    );
     trace!(?command, "command received");
 
     // Cancel currently ongoing idle
     if let Err(e) = state.connection.send(cancel_idle()).await {
         error!(error = ?e, "failed to cancel idle prior to sending command");
-        let _ = responder.send(Err(e.into()));
+        responder.send(Err(e.into()));
         return Err(());
     }
 
@@ -166,29 +474,22 @@ where
     match state.connection.receive().await {
         Ok(None) => return Err(()),
         Ok(Some(res)) => match res.into_single_frame() {
-            Ok(f) => {
-                if let Some(subsystem) = Subsystem::from_frame(f) {
-                    debug!(?subsystem, "state change");
-                    let _ = state
-                        .events
-                        .send(ConnectionEvent::SubsystemChange(subsystem));
-                }
-            }
+            Ok(f) => emit_changes(&state.events, &f),
             Err(e) => {
                 error!(
                     code = e.code,
                     message = e.message,
                     "idle cancel returned an error"
                 );
-                let _ = state.events.send(ConnectionEvent::ConnectionClosed(
-                    ConnectionError::InvalidResponse,
-                ));
+                let _ = state
+                    .events
+                    .send(RawEvent::Closed(ConnectionError::InvalidResponse));
                 return Err(());
             }
         },
         Err(e) => {
             error!(error = ?e, "state change error prior to sending command");
-            let _ = responder.send(Err(e.into()));
+            responder.send(Err(e.into()));
             return Err(());
         }
     }
@@ -196,10 +497,10 @@ where
     // Actually send the command. This sets the state for the next loop
     // iteration.
     match state.connection.send_list(command).await {
-        Ok(_) => state.loop_state = LoopState::WaitingForCommandReply(responder),
+        Ok(_) => state.loop_state = LoopState::WaitingForCommandReply(VecDeque::from([responder])),
         Err(e) => {
             error!(error = ?e, "failed to send command");
-            let _ = responder.send(Err(e.into()));
+            responder.send(Err(e.into()));
             return Err(());
         }
     }
@@ -208,6 +509,24 @@ where
     Ok(())
 }
 
+async fn handle_subsystems_change<C>(state: &mut State<C>) -> Result<(), ()>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+{
+    trace!("idle subsystems changed, restarting idle");
+
+    if let Err(e) = state.connection.send(cancel_idle()).await {
+        error!(error = ?e, "failed to cancel idle after subsystems change");
+        let _ = state.events.send(RawEvent::Closed(e.into()));
+        return Err(());
+    }
+
+    // The response to the cancellation is handled like a regular idle response, which also starts
+    // idling again with the new subsystems
+    let response = state.connection.receive().await;
+    handle_idle_response(state, response).await
+}
+
 async fn handle_idle_response<C>(
     state: &mut State<C>,
     response: Result<Option<Response>, MpdProtocolError>,
@@ -220,40 +539,39 @@ where
     match response {
         Ok(Some(res)) => {
             match res.into_single_frame() {
-                Ok(f) => {
-                    if let Some(subsystem) = Subsystem::from_frame(f) {
-                        debug!(?subsystem, "state change");
-                        let _ = state
-                            .events
-                            .send(ConnectionEvent::SubsystemChange(subsystem));
-                    }
-                }
+                Ok(f) => emit_changes(&state.events, &f),
                 Err(e) => {
                     error!(code = e.code, message = e.message, "idle returned an error");
-                    let _ = state.events.send(ConnectionEvent::ConnectionClosed(
-                        ConnectionError::InvalidResponse,
-                    ));
+                    let _ = state
+                        .events
+                        .send(RawEvent::Closed(ConnectionError::InvalidResponse));
                     return Err(());
                 }
             }
 
-            if let Err(e) = state.connection.send(idle()).await {
+            if let Err(e) = state.connection.send(idle(&mut state.subsystems)).await {
                 error!(error = ?e, "failed to start idling after state change");
-                let _ = state
-                    .events
-                    .send(ConnectionEvent::ConnectionClosed(e.into()));
+                let _ = state.events.send(RawEvent::Closed(e.into()));
                 return Err(());
             }
         }
         Ok(None) => return Err(()), // The connection was closed
         Err(e) => {
             error!(error = ?e, "state change error");
-            let _ = state
-                .events
-                .send(ConnectionEvent::ConnectionClosed(e.into()));
+            let _ = state.events.send(RawEvent::Closed(e.into()));
             return Err(());
         }
     }
 
     Ok(())
 }
+
+/// Send the subsystem changes contained in a response to the `idle` command, if any.
+fn emit_changes(events: &UnboundedSender<RawEvent>, frame: &Frame) {
+    let changes = Subsystem::all_from_frame(frame);
+
+    if !changes.is_empty() {
+        debug!(?changes, "state change");
+        let _ = events.send(RawEvent::Changes(changes));
+    }
+}
@@ -1,11 +1,21 @@
 //! The client implementation.
 
+mod album_art;
+mod batch;
+#[cfg(feature = "cache")]
+mod cache;
+mod config;
 mod connection;
+#[cfg(feature = "image")]
+mod thumbnail;
+mod version;
 
 use std::{
+    collections::{HashSet, VecDeque},
     fmt,
     hash::{Hash, Hasher},
     io,
+    ops::Range,
     sync::Arc,
 };
 
@@ -13,20 +23,31 @@ use bytes::BytesMut;
 use mpd_protocol::{
     AsyncConnection, MpdProtocolError,
     command::{Command as RawCommand, CommandList as RawCommandList},
-    response::{Error, Frame, Response as RawResponse},
+    response::{Error, ErrorCode, Frame, Response as RawResponse},
 };
 use tokio::{
     io::{AsyncRead, AsyncWrite},
     sync::{
-        mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
-        oneshot,
+        Semaphore,
+        mpsc::{self, UnboundedReceiver, UnboundedSender, unbounded_channel},
+        oneshot, watch,
     },
 };
 use tracing::{Instrument, Level, debug, error, span, trace, warn};
 
+#[cfg(feature = "cache")]
+pub use self::cache::AlbumArtCache;
+#[cfg(feature = "image")]
+pub use self::thumbnail::{ThumbnailCache, ThumbnailError, ThumbnailFormat, make_thumbnails};
+pub use self::{
+    album_art::AlbumArtStream,
+    config::ClientConfig,
+    version::{ProtocolVersion, ProtocolVersionError},
+};
 use crate::{
-    commands::{self as cmds, Command, CommandList},
-    responses::TypedResponseError,
+    commands::{self as cmds, ChunkedCommandList, Command, CommandList, PartialCommandList},
+    responses::{self as res, TypedResponseError},
+    tag::Tag,
 };
 
 type CommandResponder = oneshot::Sender<Result<RawResponse, CommandError>>;
@@ -102,10 +123,19 @@ pub type Connection = (Client, ConnectionEvents);
 ///
 /// Cloning the `Client` is cheap and reuses the same connection it was initially given. Dropping
 /// the last clone of a particular `Client` will close the connection automatically.
+///
+/// By default, a single connection is used both for commands and for listening to state changes,
+/// which requires interrupting the `idle` command before sending commands. If you can open two
+/// connections to the server, [`Client::connect_split`] avoids this.
 #[derive(Clone)]
 pub struct Client {
-    commands_sender: UnboundedSender<(RawCommandList, CommandResponder)>,
+    commands_sender: mpsc::Sender<(RawCommandList, CommandResponder)>,
     protocol_version: Arc<str>,
+    version: Option<ProtocolVersion>,
+    idle_subsystems: Arc<watch::Sender<Vec<Subsystem>>>,
+    tag_types: Arc<watch::Sender<Option<Vec<Tag>>>>,
+    /// The binary limit applied while connecting, which is restored after temporarily raising it.
+    binary_limit: usize,
 }
 
 impl Client {
@@ -125,10 +155,12 @@ impl Client {
     where
         C: AsyncRead + AsyncWrite + Unpin + Send + 'static,
     {
-        do_connect(connection, None).await.map_err(|e| match e {
-            ConnectWithPasswordError::ProtocolError(e) => e,
-            ConnectWithPasswordError::IncorrectPassword => unreachable!(),
-        })
+        do_connect(connection, None, &ClientConfig::default())
+            .await
+            .map_err(|e| match e {
+                ConnectWithPasswordError::ProtocolError(e) => e,
+                ConnectWithPasswordError::IncorrectPassword => unreachable!(),
+            })
     }
 
     /// Connect to the password-protected MPD server using the given connection and password.
@@ -151,7 +183,7 @@ impl Client {
     where
         C: AsyncRead + AsyncWrite + Unpin + Send + 'static,
     {
-        do_connect(connection, Some(password)).await
+        do_connect(connection, Some(password), &ClientConfig::default()).await
     }
 
     /// Connect to the possibly password-protected MPD server using the given connection and password.
@@ -174,7 +206,112 @@ impl Client {
     where
         C: AsyncRead + AsyncWrite + Unpin + Send + 'static,
     {
-        do_connect(connection, password).await
+        do_connect(connection, password, &ClientConfig::default()).await
+    }
+
+    /// Connect to the MPD server using the given connection and [configuration][ClientConfig].
+    ///
+    /// # Panics
+    ///
+    /// Since this spawns a task internally, this will panic when called outside a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// This will return an error if sending the initial commands over the given transport fails,
+    /// or if the configured password is incorrect. Failing to apply the configured binary limit or
+    /// tag types is logged, but does not result in an error.
+    pub async fn connect_with_config<C>(
+        connection: C,
+        config: ClientConfig,
+    ) -> Result<Connection, ConnectWithPasswordError>
+    where
+        C: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        do_connect(connection, config.password.as_deref(), &config).await
+    }
+
+    /// Connect to the MPD server using two separate connections, one for sending commands and one
+    /// which is dedicated to listening for [state changes][ConnectionEvent].
+    ///
+    /// Compared to [`Client::connect`], this avoids having to interrupt the `idle` command before
+    /// every command, which reduces the latency of sending commands. Apart from that, the
+    /// resulting `Client` and `ConnectionEvents` behave the same as with a single connection. Both
+    /// connections must be to the same server. If either of them is closed or fails, the other one
+    /// is closed as well, and the error is reported as a
+    /// [`ConnectionClosed`][ConnectionEvent::ConnectionClosed] event.
+    ///
+    /// The command connection is kept open by sending a `ping` when it was not used for a while,
+    /// see [`ClientConfig::keepalive_interval`].
+    ///
+    /// # Panics
+    ///
+    /// Since this spawns tasks internally, this will panic when called outside a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// This will return an error if sending the initial commands over either of the given
+    /// transports fails.
+    pub async fn connect_split<C, I>(commands: C, idle: I) -> Result<Connection, MpdProtocolError>
+    where
+        C: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+        I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        do_connect_split(commands, idle, None, &ClientConfig::default())
+            .await
+            .map_err(|e| match e {
+                ConnectWithPasswordError::ProtocolError(e) => e,
+                ConnectWithPasswordError::IncorrectPassword => unreachable!(),
+            })
+    }
+
+    /// Connect to the possibly password-protected MPD server using two separate connections and
+    /// the given password.
+    ///
+    /// See [`Client::connect_split`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Since this spawns tasks internally, this will panic when called outside a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// This will return an error if sending the initial commands over either of the given
+    /// transports fails, or if the password is incorrect.
+    pub async fn connect_split_with_password_opt<C, I>(
+        commands: C,
+        idle: I,
+        password: Option<&str>,
+    ) -> Result<Connection, ConnectWithPasswordError>
+    where
+        C: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+        I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        do_connect_split(commands, idle, password, &ClientConfig::default()).await
+    }
+
+    /// Connect to the MPD server using two separate connections and the given
+    /// [configuration][ClientConfig].
+    ///
+    /// See [`Client::connect_split`] for details. The idle resume delay of the configuration has
+    /// no effect in this mode.
+    ///
+    /// # Panics
+    ///
+    /// Since this spawns tasks internally, this will panic when called outside a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// This will return errors in the same conditions as [`Client::connect_with_config`].
+    pub async fn connect_split_with_config<C, I>(
+        commands: C,
+        idle: I,
+        config: ClientConfig,
+    ) -> Result<Connection, ConnectWithPasswordError>
+    where
+        C: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+        I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        do_connect_split(commands, idle, config.password.as_deref(), &config).await
     }
 
     /// Send a [command].
@@ -186,11 +323,17 @@ impl Client {
     /// This returns errors in the same conditions as [`Client::raw_command`], and additionally if the
     /// response fails to convert to the expected type.
     ///
+    /// If the command requires a [newer protocol version][Command::min_protocol_version] than the
+    /// server supports, this returns an [`Unsupported`][CommandError::Unsupported] error without
+    /// sending the command.
+    ///
     /// [command]: super::commands
     pub async fn command<C>(&self, cmd: C) -> Result<C::Response, CommandError>
     where
         C: Command,
     {
+        self.check_version(cmd.min_protocol_version())?;
+
         let command = cmd.command();
         let frame = self.raw_command(command).await?;
         let response = cmd.response(frame)?;
@@ -202,11 +345,14 @@ impl Client {
     /// # Errors
     ///
     /// This returns errors in the same conditions as [`Client::raw_command_list`], and
-    /// additionally if the response type conversion fails.
+    /// additionally if the response type conversion fails or if any of the commands are
+    /// [unsupported][CommandError::Unsupported] by the server.
     pub async fn command_list<L>(&self, list: L) -> Result<L::Response, CommandError>
     where
         L: CommandList,
     {
+        self.check_version(list.min_protocol_version())?;
+
         let frames = match list.command_list() {
             Some(cmds) => self.raw_command_list(cmds).await?,
             None => Vec::new(),
@@ -215,6 +361,52 @@ impl Client {
         list.responses(frames).map_err(Into::into)
     }
 
+    /// Send the given [typed command list][PartialCommandList], recovering the responses to the
+    /// commands that completed successfully if one of them fails.
+    ///
+    /// If a command in the list returns an error, the inner result is an error containing the
+    /// typed responses to the commands before it, the index and error of the failed command, and
+    /// the indices of the commands which did not run.
+    ///
+    /// # Errors
+    ///
+    /// The outer result is an error in the same conditions as [`Client::command_list`], except
+    /// for errors returned by the commands themselves.
+    pub async fn command_list_partial<L>(
+        &self,
+        list: L,
+    ) -> Result<Result<L::Response, PartialCommandListError<L::PartialResponse>>, CommandError>
+    where
+        L: PartialCommandList,
+    {
+        self.check_version(list.min_protocol_version())?;
+
+        let Some(commands) = list.command_list() else {
+            return Ok(Ok(list.responses(Vec::new())?));
+        };
+
+        let len = commands.len();
+
+        match self.raw_command_list(commands).await {
+            Ok(frames) => Ok(Ok(list.responses(frames)?)),
+            Err(CommandError::ErrorResponse {
+                error,
+                succesful_frames,
+            }) => {
+                let index = succesful_frames.len();
+                let completed = list.partial_responses(succesful_frames)?;
+
+                Ok(Err(PartialCommandListError {
+                    completed,
+                    index,
+                    error,
+                    not_run: index + 1..len,
+                }))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
     /// Send the given command, and return the response to it.
     ///
     /// # Errors
@@ -266,17 +458,147 @@ impl Client {
         Ok(frames)
     }
 
+    /// Send the given command list in chunks of at most `max_size` bytes each.
+    ///
+    /// MPD limits the size of command lists (see [`DEFAULT_MAX_COMMAND_LIST_SIZE`]), so very long
+    /// command lists fail as a whole. This splits the list using
+    /// [`chunks_by_size`][RawCommandList::chunks_by_size] and sends the chunks one after another,
+    /// calling `progress` after each successfully completed chunk.
+    ///
+    /// Note that unlike a single command list, the commands are not executed atomically: Other
+    /// clients may execute commands between the chunks.
+    ///
+    /// # Errors
+    ///
+    /// If sending a chunk fails, this stops and returns the responses to all commands which
+    /// completed successfully, together with the index of the first command which did not.
+    pub async fn raw_command_list_chunked<P>(
+        &self,
+        commands: RawCommandList,
+        max_size: usize,
+        mut progress: P,
+    ) -> Result<Vec<Frame>, ChunkedCommandError<Frame>>
+    where
+        P: FnMut(ChunkProgress),
+    {
+        let total_commands = commands.len();
+        let chunks = commands.chunks_by_size(max_size);
+        let total_chunks = chunks.len();
+
+        debug!(total_commands, total_chunks, "sending chunked command list");
+
+        let mut frames = Vec::with_capacity(total_commands);
+
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            match self.raw_command_list(chunk).await {
+                Ok(chunk_frames) => frames.extend(chunk_frames),
+                Err(CommandError::ErrorResponse {
+                    error,
+                    succesful_frames,
+                }) => {
+                    frames.extend(succesful_frames);
+
+                    return Err(ChunkedCommandError {
+                        index: frames.len(),
+                        completed: frames,
+                        error: CommandError::ErrorResponse {
+                            error,
+                            succesful_frames: Vec::new(),
+                        },
+                    });
+                }
+                Err(error) => {
+                    return Err(ChunkedCommandError {
+                        index: frames.len(),
+                        completed: frames,
+                        error,
+                    });
+                }
+            }
+
+            progress(ChunkProgress {
+                completed_commands: frames.len(),
+                total_commands,
+                completed_chunks: i + 1,
+                total_chunks,
+            });
+        }
+
+        Ok(frames)
+    }
+
+    /// Send the given commands as a [typed command list][CommandList], in chunks of at most
+    /// `max_size` bytes each.
+    ///
+    /// This accepts vectors of the same command type, as well as a
+    /// [`DynamicCommandList`][cmds::DynamicCommandList] of different commands. See
+    /// [`Client::raw_command_list_chunked`] for details.
+    ///
+    /// # Errors
+    ///
+    /// This returns errors in the same conditions as [`Client::raw_command_list_chunked`], and
+    /// additionally if converting a response fails, or if any of the commands are
+    /// [unsupported][CommandError::Unsupported] by the server.
+    pub async fn command_list_chunked<L, P>(
+        &self,
+        commands: L,
+        max_size: usize,
+        progress: P,
+    ) -> Result<Vec<L::ItemResponse>, ChunkedCommandError<L::ItemResponse>>
+    where
+        L: ChunkedCommandList,
+        P: FnMut(ChunkProgress),
+    {
+        if let Err(error) = self.check_version(commands.min_protocol_version()) {
+            return Err(ChunkedCommandError {
+                index: 0,
+                completed: Vec::new(),
+                error,
+            });
+        }
+
+        let Some(raw) = commands.command_list() else {
+            return Ok(Vec::new());
+        };
+
+        let (frames, error) = match self.raw_command_list_chunked(raw, max_size, progress).await {
+            Ok(frames) => (frames, None),
+            Err(e) => (e.completed, Some((e.index, e.error))),
+        };
+
+        let (completed, conversion_error) = commands.item_responses(frames);
+
+        if let Some(e) = conversion_error {
+            return Err(ChunkedCommandError {
+                index: completed.len(),
+                completed,
+                error: e.into(),
+            });
+        }
+
+        match error {
+            Some((index, error)) => Err(ChunkedCommandError {
+                index,
+                completed,
+                error,
+            }),
+            None => Ok(completed),
+        }
+    }
+
     /// Load album art for the given URI.
     ///
     /// # Behavior
     ///
     /// This first tries to use the [`readpicture`][cmds::AlbumArtEmbedded] command to load
     /// embedded data, before falling back to reading from a separate file using the
-    /// [`albumart`](cmds::AlbumArt) command.
+    /// [`albumart`](cmds::AlbumArt) command. On servers older than protocol version 0.22, which
+    /// do not support `readpicture`, only separate files are used.
     ///
     /// **Note**: Due to the default binary size limit of MPD being quite low, loading larger art
     /// will issue many commands and can be slow. Consider increasing the
-    /// [binary size limit][cmds::SetBinaryLimit].
+    /// [binary size limit][cmds::SetBinaryLimit], or use [`Client::album_art_stream`] to receive
+    /// the data progressively.
     ///
     /// # Return value
     ///
@@ -295,54 +617,69 @@ impl Client {
     ) -> Result<Option<(BytesMut, Option<String>)>, CommandError> {
         debug!("loading album art");
 
-        let mut out = BytesMut::new();
-        let mut expected_size = 0;
-        let mut embedded = false;
-        let mut mime = None;
-
         // Try loadding embedded album art first
-        match self.command(cmds::AlbumArtEmbedded::new(uri)).await {
-            Ok(Some(resp)) => {
-                out = resp.data;
-                expected_size = resp.size;
-                out.reserve(expected_size);
-                embedded = true;
-                mime = resp.mime;
-                debug!(length = resp.size, ?mime, "found embedded album art");
-            }
-            Ok(None) => {
-                debug!("readpicture command gave no result, falling back");
-            }
-            Err(e) => match e {
-                CommandError::ErrorResponse { error, .. } if error.code == 5 => {
-                    debug!("readpicture command unsupported, falling back");
+        if self.supports_embedded_album_art() {
+            match self.load_album_art(uri, true).await {
+                Ok(Some(art)) => return Ok(Some(art)),
+                Ok(None) => {
+                    debug!("readpicture command gave no result, falling back");
                 }
-                e => return Err(e),
-            },
+                Err(e) => match e {
+                    // The server version may be unknown
+                    CommandError::ErrorResponse { error, .. }
+                        if error.kind() == ErrorCode::Unknown =>
+                    {
+                        debug!("readpicture command unsupported, falling back");
+                    }
+                    e => return Err(e),
+                },
+            }
+        } else {
+            debug!("readpicture command unsupported by server version, falling back");
         }
 
-        if !embedded {
-            if let Some(resp) = self.command(cmds::AlbumArt::new(uri)).await? {
-                out = resp.data;
-                expected_size = resp.size;
-                out.reserve(expected_size);
-                debug!(length = expected_size, "found separate file album art");
-            } else {
-                debug!("no embedded or separate album art found");
-                return Ok(None);
-            }
+        let art = self.load_album_art(uri, false).await?;
+
+        if art.is_none() {
+            debug!("no embedded or separate album art found");
         }
 
-        while out.len() < expected_size {
-            let resp = if embedded {
-                self.command(cmds::AlbumArtEmbedded::new(uri).offset(out.len()))
-                    .await?
+        Ok(art)
+    }
+
+    /// Returns `true` if the server supports the [`readpicture`][cmds::AlbumArtEmbedded] command.
+    fn supports_embedded_album_art(&self) -> bool {
+        cmds::AlbumArtEmbedded::new("")
+            .min_protocol_version()
+            .is_none_or(|required| self.supports(required))
+    }
+
+    /// Load the complete embedded or separate file album art for the given URI.
+    async fn load_album_art(
+        &self,
+        uri: &str,
+        embedded: bool,
+    ) -> Result<Option<(BytesMut, Option<String>)>, CommandError> {
+        let load = |offset| async move {
+            if embedded {
+                self.command(cmds::AlbumArtEmbedded::new(uri).offset(offset))
+                    .await
             } else {
-                self.command(cmds::AlbumArt::new(uri).offset(out.len()))
-                    .await?
-            };
+                self.command(cmds::AlbumArt::new(uri).offset(offset)).await
+            }
+        };
 
-            if let Some(resp) = resp {
+        let Some(resp) = load(0).await? else {
+            return Ok(None);
+        };
+
+        let mut out = resp.data;
+        let expected_size = resp.size;
+        out.reserve(expected_size.saturating_sub(out.len()));
+        debug!(length = expected_size, mime = ?resp.mime, embedded, "found album art");
+
+        while out.len() < expected_size {
+            if let Some(resp) = load(out.len()).await? {
                 trace!(received = resp.data.len(), progress = out.len());
                 out.extend_from_slice(&resp.data);
             } else {
@@ -353,25 +690,246 @@ impl Client {
 
         debug!(length = expected_size, "finished loading");
 
-        Ok(Some((out, mime)))
+        Ok(Some((out, resp.mime)))
+    }
+
+    /// Query what the server and the current permission level allow.
+    ///
+    /// This combines the responses to the [`commands`][cmds::Commands],
+    /// [`notcommands`][cmds::NotCommands], [`urlhandlers`][cmds::UrlHandlers],
+    /// [`decoders`][cmds::Decoders] and [`config`][cmds::Config] commands. Since the `config`
+    /// command is only permitted on local socket connections, a permission error for it results in
+    /// [`ServerCapabilities::config`] being `None` instead of an error.
+    ///
+    /// # Errors
+    ///
+    /// This returns errors in the same conditions as [`Client::command`].
+    pub async fn capabilities(&self) -> Result<ServerCapabilities, CommandError> {
+        let (commands, not_commands, url_handlers, decoders) = self
+            .command_list((
+                cmds::Commands,
+                cmds::NotCommands,
+                cmds::UrlHandlers,
+                cmds::Decoders,
+            ))
+            .await?;
+
+        let config = match self.command(cmds::Config).await {
+            Ok(config) => Some(config),
+            Err(CommandError::ErrorResponse { error, .. })
+                if error.kind() == ErrorCode::Permission =>
+            {
+                debug!("config command not permitted");
+                None
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(ServerCapabilities {
+            commands: commands.into_iter().collect(),
+            not_commands: not_commands.into_iter().collect(),
+            url_handlers,
+            decoders,
+            config,
+        })
     }
 
-    /// Get the protocol version the underlying connection is using.
+    /// Get the protocol version the underlying connection is using, as it was sent by the server.
+    ///
+    /// See [`Client::version`] for a parsed and comparable version.
     pub fn protocol_version(&self) -> &str {
         self.protocol_version.as_ref()
     }
 
+    /// Get the parsed protocol version the underlying connection is using.
+    ///
+    /// Returns `None` if the server sent a version that could not be parsed, in which case
+    /// commands are not checked against their [minimum version][Command::min_protocol_version].
+    pub fn version(&self) -> Option<ProtocolVersion> {
+        self.version
+    }
+
+    /// Returns `true` if the server supports at least the given protocol version.
+    ///
+    /// If the server version could not be parsed, this optimistically returns `true`.
+    pub fn supports(&self, required: ProtocolVersion) -> bool {
+        self.version.is_none_or(|v| v >= required)
+    }
+
     /// Returns `true` if the connection to the server has been closed (by the server or due to an
     /// error).
     pub fn is_connection_closed(&self) -> bool {
         self.commands_sender.is_closed()
     }
 
+    /// Set the subsystems the connection listens to for [state changes][ConnectionEvent].
+    ///
+    /// By default, or when given an empty list, the connection listens to all subsystems. Changes
+    /// in other subsystems will not be reported, and will also not wake up the background task
+    /// managing the connection. This can be changed at any time and affects all clones of this
+    /// `Client`.
+    pub fn set_idle_subsystems<I>(&self, subsystems: I)
+    where
+        I: IntoIterator<Item = Subsystem>,
+    {
+        let subsystems = subsystems.into_iter().collect::<Vec<_>>();
+        debug!(?subsystems, "setting idle subsystems");
+        self.idle_subsystems.send_replace(subsystems);
+    }
+
+    /// Get the subsystems the connection currently listens to for state changes.
+    ///
+    /// An empty list means all subsystems. See [`Client::set_idle_subsystems`].
+    pub fn idle_subsystems(&self) -> Vec<Subsystem> {
+        self.idle_subsystems.borrow().clone()
+    }
+
+    /// Get the tags which were negotiated with the server.
+    ///
+    /// These are the tags the server includes in responses, as reported by the server after
+    /// applying [`ClientConfig::tag_types`] or [`Client::negotiate_tag_types`]. Returns `None` if no
+    /// tags were negotiated, in which case the server default (usually all tags) is used, or if the
+    /// server did not report the enabled tags while connecting.
+    ///
+    /// Changing the tag types by sending the [`TagTypes`][cmds::TagTypes] command directly is not
+    /// reflected here.
+    pub fn tag_types(&self) -> Option<Vec<Tag>> {
+        self.tag_types.borrow().clone()
+    }
+
+    /// Set the tags which the server includes in responses, and remember them.
+    ///
+    /// Returns the tags which are enabled afterwards, as reported by the server. This affects all
+    /// clones of this `Client`.
+    ///
+    /// # Errors
+    ///
+    /// This returns errors in the same conditions as [`Client::command`]. If an error occurs, the
+    /// remembered tags are not changed.
+    pub async fn negotiate_tag_types<I>(&self, tags: I) -> Result<Vec<Tag>, CommandError>
+    where
+        I: IntoIterator<Item = Tag>,
+    {
+        let tags = tags.into_iter().collect::<Vec<_>>();
+        debug!(?tags, "negotiating tag types");
+
+        let mut list = tag_types_commands(&tags, self.version);
+        list.add(cmds::GetEnabledTagTypes.command());
+
+        let frame = self.raw_command_list(list).await?.pop().unwrap();
+        let enabled = cmds::GetEnabledTagTypes.response(frame)?;
+
+        self.tag_types.send_replace(Some(enabled.clone()));
+        Ok(enabled)
+    }
+
+    /// Send the given command with the tag types temporarily narrowed to the given tags.
+    ///
+    /// This reduces the size of heavy responses, such as listing the entire library, when only
+    /// some tags are needed. Afterwards, the [negotiated tags][Client::tag_types] are restored. If
+    /// no tags were negotiated, the enabled tags are queried first, and restored afterwards. On
+    /// protocol versions 0.24 and later, this uses `tagtypes reset` to switch the tags with a
+    /// single command each.
+    ///
+    /// Narrowing, the command itself and restoring are sent in a single command list, so other
+    /// clones of this `Client` don't observe the narrowed tags. Only if the command fails, the
+    /// tags are restored separately. If restoring the tags fails, a warning is logged, but the
+    /// response to the command is still returned.
+    ///
+    /// ```no_run
+    /// # async fn example(client: mpd_client::Client) -> Result<(), mpd_client::client::CommandError> {
+    /// use mpd_client::{commands::ListAllIn, tag::Tag};
+    ///
+    /// let songs = client
+    ///     .command_with_tag_types(
+    ///         &[Tag::Artist, Tag::Album, Tag::Title],
+    ///         ListAllIn::root(),
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This returns errors in the same conditions as [`Client::command`]. Like there, an
+    /// [`ErrorResponse`][CommandError::ErrorResponse] refers to the given command only. If
+    /// narrowing the tags fails, the error returned by the `tagtypes` command is returned instead.
+    pub async fn command_with_tag_types<C>(
+        &self,
+        tags: &[Tag],
+        cmd: C,
+    ) -> Result<C::Response, CommandError>
+    where
+        C: Command,
+    {
+        self.check_version(cmd.min_protocol_version())?;
+
+        let negotiated = self.tag_types.borrow().clone();
+        let current = match negotiated {
+            Some(tags) => tags,
+            None => self.command(cmds::GetEnabledTagTypes).await?,
+        };
+
+        let mut list = tag_types_commands(tags, self.version);
+        let index = list.len();
+        list.add(cmd.command());
+
+        let restore = tag_types_commands(&current, self.version);
+        list.extend(restore.clone());
+
+        let frame = match self.raw_command_list(list).await {
+            Ok(frames) => frames.into_iter().nth(index).unwrap(),
+            Err(CommandError::ErrorResponse {
+                error,
+                succesful_frames,
+            }) if succesful_frames.len() > index => {
+                warn!(?error, "failed to restore tag types");
+                succesful_frames.into_iter().nth(index).unwrap()
+            }
+            Err(CommandError::ErrorResponse {
+                mut error,
+                succesful_frames,
+            }) => {
+                if succesful_frames.len() < index {
+                    debug!(?error, "failed to narrow tag types");
+                }
+
+                // Restoring the tags did not run
+                if let Err(e) = self.raw_command_list(restore).await {
+                    warn!(error = ?e, "failed to restore tag types");
+                }
+
+                // Make the error refer to the single command
+                error.command_index = 0;
+
+                return Err(CommandError::ErrorResponse {
+                    error,
+                    succesful_frames: Vec::new(),
+                });
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(cmd.response(frame)?)
+    }
+
+    fn check_version(&self, required: Option<ProtocolVersion>) -> Result<(), CommandError> {
+        match (required, self.version) {
+            (Some(required), Some(server)) if server < required => {
+                debug!(%required, %server, "command unsupported by server");
+                Err(CommandError::Unsupported { required, server })
+            }
+            _ => Ok(()),
+        }
+    }
+
     async fn do_send(&self, commands: RawCommandList) -> Result<RawResponse, CommandError> {
         let (tx, rx) = oneshot::channel();
 
         self.commands_sender
             .send((commands, tx))
+            .await
             .map_err(|_| CommandError::ConnectionClosed)?;
 
         rx.await.map_err(|_| CommandError::ConnectionClosed)?
@@ -390,13 +948,105 @@ impl fmt::Debug for Client {
 async fn do_connect<IO: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
     io: IO,
     password: Option<&str>,
+    config: &ClientConfig,
 ) -> Result<Connection, ConnectWithPasswordError> {
     let span = span!(Level::DEBUG, "client connection");
 
-    let (state_changes_sender, state_changes) = unbounded_channel();
-    let (commands_sender, commands_receiver) = unbounded_channel();
+    let mut connection = handshake(io, password, config, &span).await?;
+    let version = parse_version(connection.protocol_version(), &span);
+    let configured = configure(&mut connection, version, config, &span).await?;
 
-    let mut connection = match AsyncConnection::connect(io).instrument(span.clone()).await {
+    let (client, state_changes, channels) =
+        new_client(connection.protocol_version(), version, configured, config);
+
+    tokio::spawn(
+        connection::run_loop(
+            connection,
+            channels.commands,
+            channels.events,
+            channels.idle_subsystems,
+            config.run_loop_options(),
+        )
+        .instrument(span!(parent: &span, Level::TRACE, "run loop")),
+    );
+
+    Ok((client, state_changes))
+}
+
+/// Perform the initial handshake on both connections used in split mode.
+async fn do_connect_split<C, I>(
+    commands_io: C,
+    idle_io: I,
+    password: Option<&str>,
+    config: &ClientConfig,
+) -> Result<Connection, ConnectWithPasswordError>
+where
+    C: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let span = span!(Level::DEBUG, "client connection", split = true);
+
+    let mut commands_connection = handshake(commands_io, password, config, &span).await?;
+    let idle_connection = handshake(idle_io, password, config, &span).await?;
+
+    if commands_connection.protocol_version() != idle_connection.protocol_version() {
+        warn!(
+            parent: &span,
+            commands = commands_connection.protocol_version(),
+            idle = idle_connection.protocol_version(),
+            "connections use different protocol versions"
+        );
+    }
+
+    let version = parse_version(commands_connection.protocol_version(), &span);
+    let configured = configure(&mut commands_connection, version, config, &span).await?;
+
+    let (client, state_changes, channels) = new_client(
+        commands_connection.protocol_version(),
+        version,
+        configured,
+        config,
+    );
+
+    let (command_link, idle_link) = connection::Link::new();
+
+    tokio::spawn(
+        connection::run_command_loop(
+            commands_connection,
+            channels.commands,
+            channels.events.clone(),
+            command_link,
+            config.run_loop_options(),
+        )
+        .instrument(span!(parent: &span, Level::TRACE, "command loop")),
+    );
+
+    tokio::spawn(
+        connection::run_idle_loop(
+            idle_connection,
+            channels.events,
+            channels.idle_subsystems,
+            idle_link,
+        )
+        .instrument(span!(parent: &span, Level::TRACE, "idle loop")),
+    );
+
+    Ok((client, state_changes))
+}
+
+/// Perform the protocol handshake on a single connection, including sending the password.
+async fn handshake<IO: AsyncRead + AsyncWrite + Unpin>(
+    io: IO,
+    password: Option<&str>,
+    config: &ClientConfig,
+    span: &tracing::Span,
+) -> Result<AsyncConnection<IO>, ConnectWithPasswordError> {
+    let connection = match config.buffer_capacity {
+        Some(capacity) => AsyncConnection::connect_with_buffer_capacity(io, capacity).await,
+        None => AsyncConnection::connect(io).await,
+    };
+
+    let mut connection = match connection {
         Ok(c) => c,
         Err(e) => {
             error!(error = ?e, "failed to perform initial handshake");
@@ -404,28 +1054,26 @@ async fn do_connect<IO: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
         }
     };
 
-    let protocol_version = Arc::from(connection.protocol_version());
-
     if let Some(password) = password {
-        trace!(parent: &span, "sending password");
+        trace!(parent: span, "sending password");
 
         if let Err(e) = connection
             .send(RawCommand::new("password").argument(password.to_owned()))
             .instrument(span.clone())
             .await
         {
-            error!(parent: &span, error = ?e, "failed to send password");
+            error!(parent: span, error = ?e, "failed to send password");
             return Err(e.into());
         }
 
         match connection.receive().instrument(span.clone()).await {
             Err(e) => {
-                error!(parent: &span, error = ?e, "failed to receive reply to password");
+                error!(parent: span, error = ?e, "failed to receive reply to password");
                 return Err(e.into());
             }
             Ok(None) => {
                 error!(
-                    parent: &span,
+                    parent: span,
                     "unexpected end of stream after sending password"
                 );
                 return Err(MpdProtocolError::Io(io::Error::new(
@@ -435,31 +1083,262 @@ async fn do_connect<IO: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
                 .into());
             }
             Ok(Some(response)) if response.is_error() => {
-                error!(parent: &span, "incorrect password");
+                error!(parent: span, "incorrect password");
                 return Err(ConnectWithPasswordError::IncorrectPassword);
             }
             Ok(Some(_)) => {
-                trace!(parent: &span, "password accepted");
+                trace!(parent: span, "password accepted");
             }
         }
     }
 
-    tokio::spawn(
-        connection::run_loop(connection, commands_receiver, state_changes_sender)
-            .instrument(span!(parent: &span, Level::TRACE, "run loop")),
-    );
+    Ok(connection)
+}
+
+/// The settings which were applied to a freshly connected connection.
+struct Configured {
+    /// The negotiated tags.
+    tag_types: Option<Vec<Tag>>,
+    /// The binary limit, if the server accepted a configured one.
+    binary_limit: Option<usize>,
+}
+
+/// Apply the binary limit and tag types from the given configuration to a freshly connected
+/// connection.
+///
+/// Failures are logged but otherwise ignored, except for protocol errors. The binary limit is set
+/// separately, so that the tags are negotiated even if the server rejects it.
+async fn configure<IO: AsyncRead + AsyncWrite + Unpin>(
+    connection: &mut AsyncConnection<IO>,
+    version: Option<ProtocolVersion>,
+    config: &ClientConfig,
+    span: &tracing::Span,
+) -> Result<Configured, MpdProtocolError> {
+    let mut binary_limit = None;
+
+    if let Some(limit) = config.binary_limit
+        && set_binary_limit(connection, version, limit, span).await?
+    {
+        binary_limit = Some(limit);
+    }
+
+    let tag_types = match &config.tag_types {
+        Some(tags) => apply_tag_types(connection, version, tags, span).await?,
+        None => None,
+    };
+
+    Ok(Configured {
+        tag_types,
+        binary_limit,
+    })
+}
+
+/// Set the binary limit, returning `true` if the server accepted it.
+async fn set_binary_limit<IO: AsyncRead + AsyncWrite + Unpin>(
+    connection: &mut AsyncConnection<IO>,
+    version: Option<ProtocolVersion>,
+    limit: usize,
+    span: &tracing::Span,
+) -> Result<bool, MpdProtocolError> {
+    let command = cmds::SetBinaryLimit(limit);
+
+    if let (Some(required), Some(server)) = (command.min_protocol_version(), version)
+        && server < required
+    {
+        warn!(parent: span, %server, "server does not support binarylimit, ignoring");
+        return Ok(false);
+    }
+
+    trace!(parent: span, limit, "setting binary limit");
+
+    let response = connection
+        .command(command.command())
+        .instrument(span.clone())
+        .await?;
+
+    match response.into_single_frame() {
+        Ok(_) => Ok(true),
+        Err(error) => {
+            warn!(
+                parent: span,
+                code = error.code,
+                message = &*error.message,
+                "failed to set binary limit"
+            );
+            Ok(false)
+        }
+    }
+}
+
+/// Enable exactly the given tags, returning the tags the server reports as enabled afterwards, or
+/// `None` if they can't be determined.
+async fn apply_tag_types<IO: AsyncRead + AsyncWrite + Unpin>(
+    connection: &mut AsyncConnection<IO>,
+    version: Option<ProtocolVersion>,
+    tags: &[Tag],
+    span: &tracing::Span,
+) -> Result<Option<Vec<Tag>>, MpdProtocolError> {
+    let mut list = tag_types_commands(tags, version);
+    list.add(cmds::GetEnabledTagTypes.command());
+
+    trace!(parent: span, ?list, "negotiating tag types");
+
+    let response = connection
+        .command_list(list)
+        .instrument(span.clone())
+        .await?;
+
+    let mut last = None;
+
+    for frame in response {
+        match frame {
+            Ok(frame) => last = Some(frame),
+            Err(error) => {
+                warn!(
+                    parent: span,
+                    code = error.code,
+                    message = &*error.message,
+                    "failed to enable tag types"
+                );
+                last = None;
+                break;
+            }
+        }
+    }
+
+    // The tags may have been changed partially, so ask for the ones which are actually enabled
+    let frame = match last {
+        Some(frame) => frame,
+        None => match connection
+            .command(cmds::GetEnabledTagTypes.command())
+            .instrument(span.clone())
+            .await?
+            .into_single_frame()
+        {
+            Ok(frame) => frame,
+            Err(error) => {
+                warn!(
+                    parent: span,
+                    code = error.code,
+                    message = &*error.message,
+                    "failed to query enabled tag types"
+                );
+                return Ok(None);
+            }
+        },
+    };
+
+    match cmds::GetEnabledTagTypes.response(frame) {
+        Ok(enabled) => {
+            trace!(parent: span, tags = ?enabled, "negotiated tag types");
+            Ok(Some(enabled))
+        }
+        Err(e) => {
+            warn!(parent: span, error = ?e, "invalid response to tagtypes");
+            Ok(None)
+        }
+    }
+}
+
+/// Create the commands to enable exactly the given tags.
+fn tag_types_commands(tags: &[Tag], version: Option<ProtocolVersion>) -> RawCommandList {
+    let supports_reset = version.is_some_and(|v| v >= ProtocolVersion::new(0, 24, 0));
+
+    match tags {
+        [] => RawCommandList::new(cmds::TagTypes::disable_all().command()),
+        tags if supports_reset => RawCommandList::new(cmds::TagTypes::reset(tags).command()),
+        tags => {
+            let mut list = RawCommandList::new(cmds::TagTypes::disable_all().command());
+            list.add(cmds::TagTypes::enable(tags).command());
+            list
+        }
+    }
+}
+
+/// Parse the protocol version sent by the server, logging a warning if it is invalid.
+fn parse_version(raw: &str, span: &tracing::Span) -> Option<ProtocolVersion> {
+    match raw.parse() {
+        Ok(v) => Some(v),
+        Err(e) => {
+            warn!(parent: span, error = %e, "failed to parse protocol version");
+            None
+        }
+    }
+}
+
+/// The background task halves of the channels connected to a [`Client`].
+struct ClientChannels {
+    commands: mpsc::Receiver<(RawCommandList, CommandResponder)>,
+    events: UnboundedSender<RawEvent>,
+    idle_subsystems: watch::Receiver<Vec<Subsystem>>,
+}
+
+/// Create a new `Client` for a connection using the given protocol version.
+fn new_client(
+    protocol_version: &str,
+    version: Option<ProtocolVersion>,
+    configured: Configured,
+    config: &ClientConfig,
+) -> (Client, ConnectionEvents, ClientChannels) {
+    let (events_sender, events) = unbounded_channel();
+    // An "unbounded" queue is a bounded one with the largest possible capacity
+    let (commands_sender, commands) =
+        mpsc::channel(config.command_queue_size.unwrap_or(Semaphore::MAX_PERMITS));
+    let (idle_subsystems, idle_subsystems_receiver) = watch::channel(Vec::new());
 
-    let state_changes = ConnectionEvents(state_changes);
     let client = Client {
         commands_sender,
-        protocol_version,
+        protocol_version: Arc::from(protocol_version),
+        version,
+        idle_subsystems: Arc::new(idle_subsystems),
+        tag_types: Arc::new(watch::Sender::new(configured.tag_types)),
+        binary_limit: configured
+            .binary_limit
+            .unwrap_or(album_art::DEFAULT_BINARY_LIMIT),
     };
 
-    Ok((client, state_changes))
+    let channels = ClientChannels {
+        commands,
+        events: events_sender,
+        idle_subsystems: idle_subsystems_receiver,
+    };
+
+    (client, ConnectionEvents::new(events), channels)
+}
+
+/// What the connected server and the current permission level allow.
+///
+/// Returned by [`Client::capabilities`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ServerCapabilities {
+    /// Commands the client is permitted to use.
+    pub commands: HashSet<String>,
+    /// Commands the server supports, but the client is not permitted to use.
+    pub not_commands: HashSet<String>,
+    /// Supported URL schemes.
+    pub url_handlers: Vec<String>,
+    /// Available decoder plugins.
+    pub decoders: Vec<res::Decoder>,
+    /// Server configuration, only available on local socket connections.
+    pub config: Option<res::Config>,
+}
+
+impl ServerCapabilities {
+    /// Returns `true` if the client is permitted to use the given command.
+    pub fn has_command(&self, command: &str) -> bool {
+        self.commands.contains(command)
+    }
+
+    /// Returns `true` if the server supports the given URL scheme (e.g. `"http://"`).
+    pub fn has_url_handler(&self, scheme: &str) -> bool {
+        self.url_handlers.iter().any(|h| h == scheme)
+    }
 }
 
 /// Errors which can occur when issuing a command.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum CommandError {
     /// The connection to MPD was closed cleanly
     ConnectionClosed,
@@ -474,6 +1353,28 @@ pub enum CommandError {
     },
     /// A [typed command](crate::commands) failed to convert its response.
     InvalidTypedResponse(TypedResponseError),
+    /// The command was not executed because an earlier command in the same automatically
+    /// created batch returned an error.
+    ///
+    /// See [`ClientConfig::batch_commands`].
+    BatchAborted,
+    /// A [typed command](crate::commands) requires a newer protocol version than the server
+    /// supports. The command was not sent.
+    Unsupported {
+        /// The protocol version required by the command.
+        required: ProtocolVersion,
+        /// The protocol version of the server.
+        server: ProtocolVersion,
+    },
+    /// The response would be larger than the given maximum size.
+    ///
+    /// See [`Client::album_art_stream`].
+    TooLarge {
+        /// The size of the response, in bytes.
+        size: usize,
+        /// The maximum size, in bytes.
+        max_size: usize,
+    },
 }
 
 impl fmt::Display for CommandError {
@@ -484,6 +1385,17 @@ impl fmt::Display for CommandError {
             CommandError::InvalidTypedResponse(_) => {
                 write!(f, "response was invalid for typed command")
             }
+            CommandError::BatchAborted => {
+                write!(f, "an earlier command in the same batch returned an error")
+            }
+            CommandError::Unsupported { required, server } => write!(
+                f,
+                "command requires protocol version {required}, but the server uses {server}"
+            ),
+            CommandError::TooLarge { size, max_size } => write!(
+                f,
+                "response of {size} bytes exceeds the maximum size of {max_size} bytes"
+            ),
             CommandError::ErrorResponse {
                 error,
                 succesful_frames,
@@ -504,6 +1416,43 @@ impl fmt::Display for CommandError {
     }
 }
 
+impl CommandError {
+    /// Returns the [error code][ErrorCode] if this is an [`ErrorResponse`].
+    ///
+    /// [`ErrorResponse`]: CommandError::ErrorResponse
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        match self {
+            CommandError::ErrorResponse { error, .. } => Some(error.kind()),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the server responded that the requested resource (e.g. a song, playlist
+    /// or sticker) does not exist.
+    pub fn is_not_found(&self) -> bool {
+        self.error_code() == Some(ErrorCode::NoExist)
+    }
+
+    /// Returns `true` if the server refused the command due to insufficient permissions.
+    ///
+    /// This may be resolved by [connecting with a password][Client::connect_with_password].
+    pub fn is_permission_denied(&self) -> bool {
+        self.error_code() == Some(ErrorCode::Permission)
+    }
+
+    /// Returns `true` if the error is caused by a transient server state, meaning that sending
+    /// the same command again later may succeed.
+    ///
+    /// This is the case if a database update is already in progress, or the player was not in a
+    /// state to execute the command.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.error_code(),
+            Some(ErrorCode::UpdateAlready | ErrorCode::PlayerSync)
+        )
+    }
+}
+
 impl std::error::Error for CommandError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
@@ -528,6 +1477,78 @@ impl From<TypedResponseError> for CommandError {
     }
 }
 
+/// The default maximum size of command lists accepted by MPD, in bytes.
+///
+/// This can be changed in the server configuration using the `max_command_list_size` option.
+pub const DEFAULT_MAX_COMMAND_LIST_SIZE: usize = 2048 * 1024;
+
+/// Progress of a [chunked command list][Client::raw_command_list_chunked].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChunkProgress {
+    /// Number of commands completed so far.
+    pub completed_commands: usize,
+    /// Total number of commands.
+    pub total_commands: usize,
+    /// Number of chunks completed so far.
+    pub completed_chunks: usize,
+    /// Total number of chunks.
+    pub total_chunks: usize,
+}
+
+/// Error returned when sending a [chunked command list][Client::raw_command_list_chunked] fails.
+#[derive(Debug)]
+pub struct ChunkedCommandError<T> {
+    /// Index of the command at which sending the list stopped.
+    ///
+    /// All commands before this index completed successfully. If the error is an
+    /// [`ErrorResponse`][CommandError::ErrorResponse], this is the command which returned the
+    /// error. Otherwise, it is the first command of the chunk which failed to send, and it is
+    /// unknown whether the commands of that chunk were executed.
+    pub index: usize,
+    /// The responses to the commands which completed successfully.
+    pub completed: Vec<T>,
+    /// The error that occurred.
+    pub error: CommandError,
+}
+
+impl<T> fmt::Display for ChunkedCommandError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "chunked command list stopped at index {}", self.index)
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for ChunkedCommandError<T> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// A command in a [typed command list][Client::command_list_partial] returned an error.
+#[derive(Debug)]
+pub struct PartialCommandListError<T> {
+    /// The responses to the commands which completed successfully.
+    pub completed: T,
+    /// The index of the command which returned the error.
+    pub index: usize,
+    /// The error returned by the command.
+    pub error: Error,
+    /// The indices of the commands which were not executed because of the error.
+    pub not_run: Range<usize>,
+}
+
+impl<T> fmt::Display for PartialCommandListError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "command at index {} returned an error [code {}]: {}",
+            self.index, self.error.code, self.error.message
+        )
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for PartialCommandListError<T> {}
+
 /// Error returned when [connecting with a password][Client::connect_with_password] fails.
 #[derive(Debug)]
 pub enum ConnectWithPasswordError {
@@ -567,17 +1588,66 @@ impl From<MpdProtocolError> for ConnectWithPasswordError {
 /// This includes notifications about state changes as well as the connection being closed,
 /// possibly due to an error. If you don't care about these, you can just drop this receiver.
 #[derive(Debug)]
-pub struct ConnectionEvents(pub(crate) UnboundedReceiver<ConnectionEvent>);
+pub struct ConnectionEvents {
+    receiver: UnboundedReceiver<RawEvent>,
+    pending: VecDeque<Subsystem>,
+}
 
 impl ConnectionEvents {
+    pub(crate) fn new(receiver: UnboundedReceiver<RawEvent>) -> Self {
+        Self {
+            receiver,
+            pending: VecDeque::new(),
+        }
+    }
+
     /// Wait for the next connection event.
     ///
+    /// If multiple subsystems changed at the same time, a separate
+    /// [`SubsystemChange`][ConnectionEvent::SubsystemChange] event is returned for each of them.
+    ///
     /// If this returns `None`, the connection was closed cleanly.
     pub async fn next(&mut self) -> Option<ConnectionEvent> {
-        self.0.recv().await
+        loop {
+            if let Some(subsystem) = self.pending.pop_front() {
+                return Some(ConnectionEvent::SubsystemChange(subsystem));
+            }
+
+            match self.receiver.recv().await? {
+                RawEvent::Changes(changes) => self.pending.extend(changes),
+                RawEvent::Closed(error) => return Some(ConnectionEvent::ConnectionClosed(error)),
+            }
+        }
+    }
+
+    /// Wait for the next set of subsystem changes.
+    ///
+    /// This returns all subsystems which were reported as changed at the same time, in the order
+    /// they were sent by the server. If some changes from the set were already returned by
+    /// [`ConnectionEvents::next`], only the remaining ones are returned.
+    ///
+    /// If this returns `None`, the connection was closed cleanly.
+    pub async fn next_changes(&mut self) -> Option<Result<Vec<Subsystem>, ConnectionError>> {
+        if !self.pending.is_empty() {
+            return Some(Ok(self.pending.drain(..).collect()));
+        }
+
+        match self.receiver.recv().await? {
+            RawEvent::Changes(changes) => Some(Ok(changes)),
+            RawEvent::Closed(error) => Some(Err(error)),
+        }
     }
 }
 
+/// Events sent from the run loop to [`ConnectionEvents`].
+#[derive(Debug)]
+pub(crate) enum RawEvent {
+    /// Subsystems changed, as reported by a single `idle` response.
+    Changes(Vec<Subsystem>),
+    /// The connection was closed because of an error.
+    Closed(ConnectionError),
+}
+
 /// Events that occur during connection life cycle.
 #[derive(Debug)]
 pub enum ConnectionEvent {
@@ -617,8 +1687,8 @@ pub enum Subsystem {
 }
 
 impl Subsystem {
-    fn from_frame(mut r: Frame) -> Option<Subsystem> {
-        r.get("changed").map(|raw| match &*raw {
+    fn from_raw(raw: &str) -> Subsystem {
+        match raw {
             "database" => Subsystem::Database,
             "message" => Subsystem::Message,
             "mixer" => Subsystem::Mixer,
@@ -634,7 +1704,16 @@ impl Subsystem {
             "neighbor" => Subsystem::Neighbor,
             "mount" => Subsystem::Mount,
             _ => Subsystem::Other(raw.into()),
-        })
+        }
+    }
+
+    /// Parse all changed subsystems contained in a response to the `idle` command.
+    fn all_from_frame(frame: &Frame) -> Vec<Subsystem> {
+        frame
+            .fields()
+            .filter(|&(key, _)| key == "changed")
+            .map(|(_, raw)| Subsystem::from_raw(raw))
+            .collect()
     }
 
     /// Returns the raw protocol name used for this subsystem.
@@ -708,12 +1787,14 @@ impl From<MpdProtocolError> for ConnectionError {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::hash_map::DefaultHasher;
+    use std::{collections::hash_map::DefaultHasher, time::Duration};
 
     use assert_matches::assert_matches;
+    use bytes::Bytes;
     use tokio_test::io::Builder as MockBuilder;
 
     use super::*;
+    use crate::{commands::SongId, tag::Tag};
 
     static GREETING: &[u8] = b"OK MPD 0.21.11\n";
 
@@ -735,148 +1816,1019 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn command() {
+    async fn multiple_state_changes() {
         let io = MockBuilder::new()
             .read(GREETING)
             .write(b"idle\n")
-            .write(b"noidle\n")
-            .read(b"changed: playlist\nOK\n")
-            .write(b"hello\n")
-            .read(b"foo: bar\nOK\n")
+            .read(b"changed: player\nchanged: mixer\nOK\n")
+            .write(b"idle\n")
+            .read(b"changed: playlist\nchanged: options\nOK\n")
             .write(b"idle\n")
             .build();
 
-        let (client, mut state_changes) = Client::connect(io).await.expect("connect failed");
-
-        let response = client
-            .raw_command(RawCommand::new("hello"))
-            .await
-            .expect("command failed");
+        let (_client, mut state_changes) = Client::connect(io).await.expect("connect failed");
 
-        assert_eq!(response.find("foo"), Some("bar"));
         assert_matches!(
             state_changes.next().await,
-            Some(ConnectionEvent::SubsystemChange(Subsystem::Queue))
+            Some(ConnectionEvent::SubsystemChange(Subsystem::Player))
         );
-        assert!(state_changes.next().await.is_none());
+        assert_matches!(
+            state_changes.next().await,
+            Some(ConnectionEvent::SubsystemChange(Subsystem::Mixer))
+        );
+
+        assert_eq!(
+            state_changes.next_changes().await.unwrap().unwrap(),
+            [Subsystem::Queue, Subsystem::Options]
+        );
+        assert!(state_changes.next_changes().await.is_none());
     }
 
     #[tokio::test]
-    async fn incomplete_response() {
+    async fn partially_consumed_state_changes() {
         let io = MockBuilder::new()
             .read(GREETING)
             .write(b"idle\n")
-            .write(b"noidle\n")
-            .read(b"OK\n")
-            .write(b"hello\n")
-            .read(b"foo: bar\n")
-            .read(b"baz: qux\nOK\n")
+            .read(b"changed: player\nchanged: mixer\nchanged: output\nOK\n")
             .write(b"idle\n")
             .build();
 
-        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
-
-        let response = client
-            .raw_command(RawCommand::new("hello"))
-            .await
-            .expect("command failed");
+        let (_client, mut state_changes) = Client::connect(io).await.expect("connect failed");
 
-        assert_eq!(response.find("foo"), Some("bar"));
+        assert_matches!(
+            state_changes.next().await,
+            Some(ConnectionEvent::SubsystemChange(Subsystem::Player))
+        );
+        assert_eq!(
+            state_changes.next_changes().await.unwrap().unwrap(),
+            [Subsystem::Mixer, Subsystem::Output]
+        );
     }
 
     #[tokio::test]
-    async fn command_list() {
+    async fn idle_subsystems() {
         let io = MockBuilder::new()
             .read(GREETING)
             .write(b"idle\n")
             .write(b"noidle\n")
-            .read(b"OK\n")
-            .write(b"command_list_ok_begin\nfoo\nbar\ncommand_list_end\n")
-            .read(b"foo: asdf\nlist_OK\n")
-            .read(b"baz: qux\nlist_OK\nOK\n")
-            .write(b"idle\n")
+            .read(b"changed: mixer\nOK\n")
+            .write(b"idle player options\n")
+            .read(b"changed: player\nOK\n")
+            .write(b"idle player options\n")
             .build();
 
-        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
-
-        let mut commands = RawCommandList::new(RawCommand::new("foo"));
-        commands.add(RawCommand::new("bar"));
-
-        let responses = client
-            .raw_command_list(commands)
-            .await
-            .expect("command failed");
-
-        assert_eq!(responses.len(), 2);
-        assert_eq!(responses[0].find("foo"), Some("asdf"));
-    }
-
-    #[tokio::test]
-    async fn dropping_client() {
-        let io = MockBuilder::new().read(GREETING).write(b"idle\n").build();
-
         let (client, mut state_changes) = Client::connect(io).await.expect("connect failed");
 
-        drop(client);
+        // let the run loop send the initial idle command
+        tokio::task::yield_now().await;
 
-        assert!(state_changes.next().await.is_none());
+        client.set_idle_subsystems([Subsystem::Player, Subsystem::Options]);
+        assert_eq!(
+            client.idle_subsystems(),
+            [Subsystem::Player, Subsystem::Options]
+        );
+
+        assert_matches!(
+            state_changes.next().await,
+            Some(ConnectionEvent::SubsystemChange(Subsystem::Mixer))
+        );
+        assert_matches!(
+            state_changes.next().await,
+            Some(ConnectionEvent::SubsystemChange(Subsystem::Player))
+        );
     }
 
-    #[tokio::test]
-    async fn album_art() {
+    #[tokio::test(start_paused = true)]
+    async fn idle_subsystems_after_command() {
         let io = MockBuilder::new()
             .read(GREETING)
             .write(b"idle\n")
             .write(b"noidle\n")
             .read(b"OK\n")
-            .write(b"readpicture foo/bar.mp3 0\n")
-            .read(b"size: 6\ntype: image/jpeg\nbinary: 3\nFOO\nOK\n")
-            .write(b"readpicture foo/bar.mp3 3\n")
-            .read(b"size: 6\ntype: image/jpeg\nbinary: 3\nBAR\nOK\n")
+            .write(b"hello\n")
+            .read(b"OK\n")
+            .write(b"idle database\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"world\n")
+            .read(b"OK\n")
+            .write(b"idle database\n")
             .build();
 
-        let (client, _) = Client::connect(io).await.expect("connect failed");
+        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
 
-        let x = client
-            .album_art("foo/bar.mp3")
+        client
+            .raw_command(RawCommand::new("hello"))
             .await
             .expect("command failed");
 
-        assert_eq!(
-            x,
-            Some((BytesMut::from("FOOBAR"), Some(String::from("image/jpeg"))))
-        );
+        client.set_idle_subsystems([Subsystem::Database]);
+
+        // wait until the connection starts idling again
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        client
+            .raw_command(RawCommand::new("world"))
+            .await
+            .expect("command failed");
     }
 
     #[tokio::test]
-    async fn album_art_fallback() {
+    async fn command() {
         let io = MockBuilder::new()
             .read(GREETING)
             .write(b"idle\n")
             .write(b"noidle\n")
-            .read(b"OK\n")
-            .write(b"readpicture foo/bar.mp3 0\n")
-            .read(b"OK\n")
-            .write(b"albumart foo/bar.mp3 0\n")
-            .read(b"size: 6\nbinary: 3\nFOO\nOK\n")
-            .write(b"albumart foo/bar.mp3 3\n")
-            .read(b"size: 6\nbinary: 3\nBAR\nOK\n")
+            .read(b"changed: playlist\nOK\n")
+            .write(b"hello\n")
+            .read(b"foo: bar\nOK\n")
+            .write(b"idle\n")
             .build();
 
-        let (client, _) = Client::connect(io).await.expect("connect failed");
+        let (client, mut state_changes) = Client::connect(io).await.expect("connect failed");
 
-        let x = client
-            .album_art("foo/bar.mp3")
+        let response = client
+            .raw_command(RawCommand::new("hello"))
             .await
             .expect("command failed");
 
-        assert_eq!(x, Some((BytesMut::from("FOOBAR"), None)));
+        assert_eq!(response.find("foo"), Some("bar"));
+        assert_matches!(
+            state_changes.next().await,
+            Some(ConnectionEvent::SubsystemChange(Subsystem::Queue))
+        );
+        assert!(state_changes.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn split_connections() {
+        // Keep both connections open until the client is dropped
+        let (commands, _commands_handle) = MockBuilder::new()
+            .read(GREETING)
+            .write(b"hello\n")
+            .read(b"foo: bar\nOK\n")
+            .write(b"world\n")
+            .read(b"OK\n")
+            .build_with_handle();
+
+        let (idle, _idle_handle) = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .read(b"changed: player\nchanged: mixer\nOK\n")
+            .write(b"idle\n")
+            .build_with_handle();
+
+        let (client, mut state_changes) = Client::connect_split(commands, idle)
+            .await
+            .expect("connect failed");
+
+        let response = client
+            .raw_command(RawCommand::new("hello"))
+            .await
+            .expect("command failed");
+        assert_eq!(response.find("foo"), Some("bar"));
+
+        assert_eq!(
+            state_changes.next_changes().await.unwrap().unwrap(),
+            [Subsystem::Player, Subsystem::Mixer]
+        );
+
+        client
+            .raw_command(RawCommand::new("world"))
+            .await
+            .expect("command failed");
+
+        drop(client);
+        assert!(state_changes.next().await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn split_connections_keepalive() {
+        let commands = MockBuilder::new()
+            .read(GREETING)
+            .write(b"ping\n")
+            .read(b"OK\n")
+            .write(b"hello\n")
+            .read(b"OK\n")
+            .build();
+
+        let (idle, _idle_handle) = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .build_with_handle();
+
+        let config = ClientConfig::new().keepalive_interval(Duration::from_secs(10));
+        let (client, _state_changes) = Client::connect_split_with_config(commands, idle, config)
+            .await
+            .expect("connect failed");
+
+        tokio::time::sleep(Duration::from_secs(15)).await;
+
+        client
+            .raw_command(RawCommand::new("hello"))
+            .await
+            .expect("command failed");
+    }
+
+    #[tokio::test]
+    async fn split_connections_closed() {
+        let commands = MockBuilder::new().read(GREETING).build();
+
+        let (idle, _idle_handle) = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .build_with_handle();
+
+        let (client, mut state_changes) = Client::connect_split(commands, idle)
+            .await
+            .expect("connect failed");
+
+        // The command connection closing is reported, and also closes the idle connection
+        assert_matches!(
+            state_changes.next().await,
+            Some(ConnectionEvent::ConnectionClosed(ConnectionError::Protocol(
+                MpdProtocolError::Io(e)
+            ))) if e.kind() == io::ErrorKind::UnexpectedEof
+        );
+        assert!(state_changes.next().await.is_none());
+        assert!(client.is_connection_closed());
+
+        // The reverse: the idle connection closing also closes the command connection
+        let (commands, _commands_handle) = MockBuilder::new().read(GREETING).build_with_handle();
+        let idle = MockBuilder::new().read(GREETING).write(b"idle\n").build();
+
+        let (client, mut state_changes) = Client::connect_split(commands, idle)
+            .await
+            .expect("connect failed");
+
+        assert!(state_changes.next().await.is_none());
+        assert_matches!(
+            client.raw_command(RawCommand::new("ping")).await,
+            Err(CommandError::ConnectionClosed)
+        );
+    }
+
+    #[tokio::test]
+    async fn connect_with_config() {
+        let io = MockBuilder::new()
+            .read(b"OK MPD 0.23.5\n")
+            .write(b"password secret\n")
+            .read(b"OK\n")
+            .write(b"binarylimit 65536\n")
+            .read(b"OK\n")
+            .write(b"command_list_ok_begin\ntagtypes clear\ntagtypes enable Artist Title\ntagtypes\ncommand_list_end\n")
+            .read(b"list_OK\nlist_OK\ntagtype: Artist\ntagtype: Title\nlist_OK\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let config = ClientConfig::new()
+            .password("secret")
+            .binary_limit(65536)
+            .tag_types([Tag::Artist, Tag::Title]);
+
+        let (client, _state_changes) = Client::connect_with_config(io, config)
+            .await
+            .expect("connect failed");
+
+        assert_eq!(client.protocol_version(), "0.23.5");
+        assert_eq!(client.tag_types(), Some(vec![Tag::Artist, Tag::Title]));
+    }
+
+    #[tokio::test]
+    async fn connect_with_config_errors() {
+        let io = MockBuilder::new()
+            .read(b"OK MPD 0.23.5\n")
+            .write(b"binarylimit 64\n")
+            .read(b"ACK [2@0] {binarylimit} Value too small\n")
+            .write(b"command_list_ok_begin\ntagtypes clear\ntagtypes enable Artist Title\ntagtypes\ncommand_list_end\n")
+            .read(b"list_OK\nACK [2@1] {tagtypes} Unknown tag type\n")
+            .write(b"tagtypes\n")
+            .read(b"OK\n")
+            .write(b"idle\n")
+            .build();
+
+        let config = ClientConfig::new()
+            .binary_limit(64)
+            .tag_types([Tag::Artist, Tag::Title]);
+
+        let (client, _state_changes) = Client::connect_with_config(io, config)
+            .await
+            .expect("connect failed");
+
+        // Only the tags actually enabled on the server are remembered
+        assert_eq!(client.tag_types(), Some(Vec::new()));
+    }
+
+    #[tokio::test]
+    async fn connect_with_config_unsupported_binary_limit() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"command_list_ok_begin\ntagtypes clear\ntagtypes\ncommand_list_end\n")
+            .read(b"list_OK\nlist_OK\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let config = ClientConfig::new().binary_limit(65536).tag_types([]);
+
+        let (client, _state_changes) = Client::connect_with_config(io, config)
+            .await
+            .expect("connect failed");
+
+        assert_eq!(client.tag_types(), Some(Vec::new()));
+    }
+
+    #[tokio::test]
+    async fn command_with_tag_types() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"tagtypes\n")
+            .read(b"tagtype: Artist\ntagtype: Title\ntagtype: Genre\nOK\n")
+            .write(b"command_list_ok_begin\ntagtypes clear\ntagtypes enable Artist Title\nlistallinfo\ntagtypes clear\ntagtypes enable Artist Title Genre\ncommand_list_end\n")
+            .read(b"list_OK\nlist_OK\nfile: foo.flac\nArtist: Foo\nlist_OK\nlist_OK\nlist_OK\nOK\n")
+            .write(b"tagtypes\n")
+            .read(b"tagtype: Artist\ntagtype: Title\ntagtype: Genre\nOK\n")
+            .write(b"command_list_ok_begin\ntagtypes clear\ntagtypes enable Album\nlistallinfo\ntagtypes clear\ntagtypes enable Artist Title Genre\ncommand_list_end\n")
+            .read(b"list_OK\nlist_OK\nACK [50@2] {listallinfo} not found\n")
+            .write(b"command_list_ok_begin\ntagtypes clear\ntagtypes enable Artist Title Genre\ncommand_list_end\n")
+            .read(b"list_OK\nlist_OK\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
+        assert_eq!(client.tag_types(), None);
+
+        let songs = client
+            .command_with_tag_types(&[Tag::Artist, Tag::Title], cmds::ListAllIn::root())
+            .await
+            .expect("command failed");
+
+        assert_eq!(songs.len(), 1);
+        assert_eq!(songs[0].artists(), [String::from("Foo")]);
+
+        let error = client
+            .command_with_tag_types(&[Tag::Album], cmds::ListAllIn::root())
+            .await
+            .unwrap_err();
+
+        assert_matches!(
+            &error,
+            CommandError::ErrorResponse { error, succesful_frames }
+                if error.command_index == 0 && succesful_frames.is_empty()
+        );
+        assert!(error.is_not_found());
+        assert!(!client.is_connection_closed());
+    }
+
+    #[tokio::test]
+    async fn negotiate_tag_types() {
+        let io = MockBuilder::new()
+            .read(b"OK MPD 0.24.0\n")
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"command_list_ok_begin\ntagtypes reset Artist Album\ntagtypes\ncommand_list_end\n")
+            .read(b"list_OK\ntagtype: Artist\ntagtype: Album\nlist_OK\nOK\n")
+            .write(b"command_list_ok_begin\ntagtypes reset Title\nlistallinfo\ntagtypes reset Artist Album\ncommand_list_end\n")
+            .read(b"list_OK\nlist_OK\nlist_OK\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
+
+        let enabled = client
+            .negotiate_tag_types([Tag::Artist, Tag::Album])
+            .await
+            .expect("command failed");
+
+        assert_eq!(enabled, [Tag::Artist, Tag::Album]);
+        assert_eq!(client.tag_types(), Some(enabled));
+
+        let songs = client
+            .command_with_tag_types(&[Tag::Title], cmds::ListAllIn::root())
+            .await
+            .expect("command failed");
+
+        assert!(songs.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_resume_delay() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"hello\n")
+            .read(b"OK\n")
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"world\n")
+            .read(b"OK\n")
+            .write(b"idle\n")
+            .build();
+
+        let config = ClientConfig::new().idle_resume_delay(Duration::from_millis(10));
+        let (client, _state_changes) = Client::connect_with_config(io, config)
+            .await
+            .expect("connect failed");
+
+        client
+            .raw_command(RawCommand::new("hello"))
+            .await
+            .expect("command failed");
+
+        // longer than the configured delay, but shorter than the default one
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        client
+            .raw_command(RawCommand::new("world"))
+            .await
+            .expect("command failed");
+    }
+
+    #[tokio::test]
+    async fn pipelining() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"foo\n")
+            .write(b"bar\n")
+            .read(b"foo: 1\nOK\n")
+            .write(b"baz\n")
+            .read(b"bar: 2\nOK\n")
+            .read(b"baz: 3\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let config = ClientConfig::new().max_in_flight(2);
+        let (client, _state_changes) = Client::connect_with_config(io, config)
+            .await
+            .expect("connect failed");
+
+        let (foo, bar, baz) = tokio::join!(
+            client.raw_command(RawCommand::new("foo")),
+            client.raw_command(RawCommand::new("bar")),
+            client.raw_command(RawCommand::new("baz")),
+        );
+
+        assert_eq!(foo.unwrap().find("foo"), Some("1"));
+        assert_eq!(bar.unwrap().find("bar"), Some("2"));
+        assert_eq!(baz.unwrap().find("baz"), Some("3"));
+    }
+
+    #[tokio::test]
+    async fn split_connections_pipelining() {
+        let commands = MockBuilder::new()
+            .read(GREETING)
+            .write(b"foo\n")
+            .write(b"bar\n")
+            .read(b"foo: 1\nOK\n")
+            .read(b"ACK [50@0] {bar} not found\n")
+            .build();
+
+        let idle = MockBuilder::new().read(GREETING).write(b"idle\n").build();
+
+        let config = ClientConfig::new().max_in_flight(8);
+        let (client, _state_changes) = Client::connect_split_with_config(commands, idle, config)
+            .await
+            .expect("connect failed");
+
+        let (foo, bar) = tokio::join!(
+            client.raw_command(RawCommand::new("foo")),
+            client.raw_command(RawCommand::new("bar")),
+        );
+
+        assert_eq!(foo.unwrap().find("foo"), Some("1"));
+        assert!(bar.unwrap_err().is_not_found());
+    }
+
+    #[tokio::test]
+    async fn batching() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"command_list_ok_begin\nfoo\nbar\nbaz\ncommand_list_end\n")
+            .read(b"foo: 1\nlist_OK\nbar: 2\nlist_OK\nbaz: 3\nlist_OK\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let config = ClientConfig::new().batch_commands(true);
+        let (client, _state_changes) = Client::connect_with_config(io, config)
+            .await
+            .expect("connect failed");
+
+        let (foo, bar_baz) = tokio::join!(
+            client.raw_command(RawCommand::new("foo")),
+            client.raw_command_list(
+                RawCommandList::new(RawCommand::new("bar")).command(RawCommand::new("baz"))
+            ),
+        );
+
+        assert_eq!(foo.unwrap().find("foo"), Some("1"));
+
+        let bar_baz = bar_baz.unwrap();
+        assert_eq!(bar_baz.len(), 2);
+        assert_eq!(bar_baz[0].find("bar"), Some("2"));
+        assert_eq!(bar_baz[1].find("baz"), Some("3"));
+    }
+
+    #[tokio::test]
+    async fn batching_limit() {
+        let list = |name: &str| {
+            let mut list = RawCommandList::new(RawCommand::new(name));
+            list.extend((1..100).map(|_| RawCommand::new(name)));
+            list
+        };
+
+        let rendered = |names: &[&str]| {
+            let mut out = b"command_list_ok_begin\n".to_vec();
+            for name in names {
+                out.extend_from_slice(name.as_bytes());
+                out.push(b'\n');
+            }
+            out.extend_from_slice(b"command_list_end\n");
+            out
+        };
+
+        let ok = |n| {
+            let mut out = b"list_OK\n".repeat(n);
+            out.extend_from_slice(b"OK\n");
+            out
+        };
+
+        let first = [["foo"; 100].as_slice(), &["bar"]].concat();
+
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(&rendered(&first))
+            .read(&ok(101))
+            // The second list would exceed the maximum batch length
+            .write(&rendered(&["baz"; 100]))
+            .read(&ok(100))
+            .write(b"idle\n")
+            .build();
+
+        let config = ClientConfig::new().batch_commands(true);
+        let (client, _state_changes) = Client::connect_with_config(io, config)
+            .await
+            .expect("connect failed");
+
+        let (foo, bar, baz) = tokio::join!(
+            client.raw_command_list(list("foo")),
+            client.raw_command(RawCommand::new("bar")),
+            client.raw_command_list(list("baz")),
+        );
+
+        assert_eq!(foo.unwrap().len(), 100);
+        assert!(bar.is_ok());
+        assert_eq!(baz.unwrap().len(), 100);
+    }
+
+    #[tokio::test]
+    async fn batching_error() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"command_list_ok_begin\nfoo\nbar\nbaz\ncommand_list_end\n")
+            .read(b"foo: 1\nlist_OK\nACK [50@1] {bar} No such song\n")
+            .write(b"idle\n")
+            .build();
+
+        let config = ClientConfig::new().batch_commands(true);
+        let (client, _state_changes) = Client::connect_with_config(io, config)
+            .await
+            .expect("connect failed");
+
+        let (foo, bar, baz) = tokio::join!(
+            client.raw_command(RawCommand::new("foo")),
+            client.raw_command(RawCommand::new("bar")),
+            client.raw_command(RawCommand::new("baz")),
+        );
+
+        assert_eq!(foo.unwrap().find("foo"), Some("1"));
+        assert_matches!(
+            bar,
+            Err(CommandError::ErrorResponse { error, succesful_frames })
+                if error.command_index == 0 && succesful_frames.is_empty()
+        );
+        assert_matches!(baz, Err(CommandError::BatchAborted));
+    }
+
+    #[tokio::test]
+    async fn chunked_command_list() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"command_list_ok_begin\naddid a\naddid b\ncommand_list_end\n")
+            .read(b"Id: 1\nlist_OK\nId: 2\nlist_OK\nOK\n")
+            .write(b"command_list_ok_begin\naddid c\naddid d\ncommand_list_end\n")
+            .read(b"Id: 3\nlist_OK\nId: 4\nlist_OK\nOK\n")
+            .write(b"addid e\n")
+            .read(b"Id: 5\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
+
+        let commands = ["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(cmds::Add::uri)
+            .collect::<Vec<_>>();
+
+        let mut progress = Vec::new();
+        let responses = client
+            .command_list_chunked(commands, 16, |p| progress.push(p.completed_commands))
+            .await
+            .expect("command failed");
+
+        assert_eq!(responses.last(), Some(&SongId(5)));
+        assert_eq!(progress, [2, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn chunked_dynamic_command_list() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"command_list_ok_begin\naddid a\nplay\ncommand_list_end\n")
+            .read(b"Id: 1\nlist_OK\nlist_OK\nOK\n")
+            .write(b"addid b\n")
+            .read(b"Id: 2\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
+
+        let commands = cmds::DynamicCommandList::new()
+            .command(cmds::Add::uri("a"))
+            .command(cmds::Play::current())
+            .command(cmds::Add::uri("b"));
+
+        let responses = client
+            .command_list_chunked(commands, 13, |_| ())
+            .await
+            .expect("command failed");
+
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[2].downcast_ref::<SongId>(), Some(&SongId(2)));
+    }
+
+    #[tokio::test]
+    async fn chunked_command_list_error() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"command_list_ok_begin\nadd a\nadd b\ncommand_list_end\n")
+            .read(b"list_OK\nlist_OK\nOK\n")
+            .write(b"command_list_ok_begin\nadd c\nadd d\ncommand_list_end\n")
+            .read(b"list_OK\nACK [50@1] {add} No such directory\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
+
+        let mut commands = RawCommandList::new(RawCommand::new("add").argument("a"));
+        for uri in ["b", "c", "d", "e"] {
+            commands.add(RawCommand::new("add").argument(uri));
+        }
+
+        let error = client
+            .raw_command_list_chunked(commands, 12, |_| ())
+            .await
+            .expect_err("command succeeded");
+
+        assert_eq!(error.index, 3);
+        assert_eq!(error.completed.len(), 3);
+        assert!(error.error.is_not_found());
+    }
+
+    #[tokio::test]
+    async fn partial_command_list() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"command_list_ok_begin\naddid a\naddid b\nplay\ncommand_list_end\n")
+            .read(b"Id: 1\nlist_OK\nACK [50@1] {addid} No such directory\n")
+            .write(b"command_list_ok_begin\naddid a\naddid b\ncommand_list_end\n")
+            .read(b"Id: 1\nlist_OK\nId: 2\nlist_OK\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
+
+        let error = client
+            .command_list_partial((
+                cmds::Add::uri("a"),
+                cmds::Add::uri("b"),
+                cmds::Play::current(),
+            ))
+            .await
+            .expect("command failed")
+            .expect_err("command list succeeded");
+
+        assert_eq!(error.completed, (Some(SongId(1)), None, None));
+        assert_eq!(error.index, 1);
+        assert_eq!(error.error.kind(), ErrorCode::NoExist);
+        assert_eq!(error.not_run, 2..3);
+
+        let responses = client
+            .command_list_partial(vec![cmds::Add::uri("a"), cmds::Add::uri("b")])
+            .await
+            .expect("command failed")
+            .expect("command list failed");
+
+        assert_eq!(responses, [SongId(1), SongId(2)]);
+    }
+
+    #[tokio::test]
+    async fn dynamic_command_list() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"command_list_ok_begin\naddid a\nplay\ncommand_list_end\n")
+            .read(b"Id: 5\nlist_OK\nlist_OK\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
+
+        let list = cmds::DynamicCommandList::new()
+            .command(cmds::Add::uri("a"))
+            .command(cmds::Play::current());
+
+        let mut responses = client.command_list(list).await.expect("command failed");
+
+        assert_eq!(responses.len(), 2);
+        assert!(responses[1].is::<()>());
+        assert_eq!(responses[1].type_name(), "()");
+
+        let id = responses.remove(0).downcast::<()>().unwrap_err();
+        assert_eq!(id.downcast_ref::<SongId>(), Some(&SongId(5)));
+        assert_eq!(id.downcast::<SongId>().unwrap(), SongId(5));
+    }
+
+    #[cfg(feature = "derive")]
+    #[tokio::test]
+    async fn derived_command() {
+        use crate::{commands::MpdCommand, responses::FromFrame};
+
+        #[derive(Debug, PartialEq, FromFrame)]
+        struct Fingerprint {
+            #[mpd(rename = "chromaprint")]
+            fingerprint: String,
+            duration: Option<Duration>,
+            tags: Vec<String>,
+        }
+
+        #[derive(MpdCommand)]
+        #[mpd(command = "getfingerprint", response = Fingerprint, min_version = "0.21")]
+        struct GetFingerprint<'a> {
+            uri: &'a str,
+            #[mpd(skip)]
+            _unused: (),
+            extra: Option<u32>,
+        }
+
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"getfingerprint foo.flac 3\n")
+            .read(b"chromaprint: abc\ntags: Artist\ntags: Album\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
+
+        let command = GetFingerprint {
+            uri: "foo.flac",
+            _unused: (),
+            extra: Some(3),
+        };
+        assert_eq!(
+            command.min_protocol_version(),
+            Some(ProtocolVersion::new(0, 21, 0))
+        );
+
+        let response = client.command(command).await.expect("command failed");
+
+        assert_eq!(
+            response,
+            Fingerprint {
+                fingerprint: String::from("abc"),
+                duration: None,
+                tags: vec![String::from("Artist"), String::from("Album")],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn status() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"status\n")
+            .read(b"volume: -1\nrepeat: 0\nrandom: 0\nsingle: 0\nconsume: oneshot\n")
+            .read(b"mixrampdb: -17.5\nmixrampdelay: 1.500\nlastloadedplaylist: foo\n")
+            .read(b"state: play\naudio: dsd64:2\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
+
+        let status = client.command(cmds::Status).await.expect("command failed");
+
+        assert_eq!(status.volume, None);
+        assert_eq!(status.consume, cmds::ConsumeMode::Oneshot);
+        assert_eq!(status.mixramp_db, Some(-17.5));
+        assert_eq!(status.mixramp_delay, Some(Duration::from_millis(1500)));
+        assert_eq!(status.last_loaded_playlist.as_deref(), Some("foo"));
+
+        let audio = status.audio.expect("no audio format");
+        assert_eq!(audio.dsd_rate(), Some(64));
+        assert_eq!(audio.channels, Some(2));
+
+        let volume = client.command(cmds::GetVolume).await;
+        assert_matches!(volume, Err(CommandError::Unsupported { .. }));
+    }
+
+    #[tokio::test]
+    async fn incomplete_response() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"hello\n")
+            .read(b"foo: bar\n")
+            .read(b"baz: qux\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
+
+        let response = client
+            .raw_command(RawCommand::new("hello"))
+            .await
+            .expect("command failed");
+
+        assert_eq!(response.find("foo"), Some("bar"));
+    }
+
+    #[tokio::test]
+    async fn command_list() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"command_list_ok_begin\nfoo\nbar\ncommand_list_end\n")
+            .read(b"foo: asdf\nlist_OK\n")
+            .read(b"baz: qux\nlist_OK\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
+
+        let mut commands = RawCommandList::new(RawCommand::new("foo"));
+        commands.add(RawCommand::new("bar"));
+
+        let responses = client
+            .raw_command_list(commands)
+            .await
+            .expect("command failed");
+
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0].find("foo"), Some("asdf"));
+    }
+
+    #[tokio::test]
+    async fn error_classification() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"foo\n")
+            .read(b"ACK [50@0] {foo} No such song\n")
+            .write(b"bar\n")
+            .read(b"ACK [54@0] {bar} already updating\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
+
+        let error = client
+            .raw_command(RawCommand::new("foo"))
+            .await
+            .expect_err("command succeeded");
+
+        assert_eq!(error.error_code(), Some(ErrorCode::NoExist));
+        assert!(error.is_not_found());
+        assert!(!error.is_permission_denied());
+        assert!(!error.is_retryable());
+
+        let error = client
+            .raw_command(RawCommand::new("bar"))
+            .await
+            .expect_err("command succeeded");
+
+        assert!(!error.is_not_found());
+        assert!(error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn dropping_client() {
+        let io = MockBuilder::new().read(GREETING).write(b"idle\n").build();
+
+        let (client, mut state_changes) = Client::connect(io).await.expect("connect failed");
+
+        drop(client);
+
+        assert!(state_changes.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn album_art() {
+        let io = MockBuilder::new()
+            .read(b"OK MPD 0.23.5\n")
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"readpicture foo/bar.mp3 0\n")
+            .read(b"size: 6\ntype: image/jpeg\nbinary: 3\nFOO\nOK\n")
+            .write(b"readpicture foo/bar.mp3 3\n")
+            .read(b"size: 6\ntype: image/jpeg\nbinary: 3\nBAR\nOK\n")
+            .build();
+
+        let (client, _) = Client::connect(io).await.expect("connect failed");
+
+        let x = client
+            .album_art("foo/bar.mp3")
+            .await
+            .expect("command failed");
+
+        assert_eq!(
+            x,
+            Some((BytesMut::from("FOOBAR"), Some(String::from("image/jpeg"))))
+        );
+    }
+
+    #[tokio::test]
+    async fn album_art_fallback() {
+        let io = MockBuilder::new()
+            .read(b"OK MPD 0.23.5\n")
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"readpicture foo/bar.mp3 0\n")
+            .read(b"OK\n")
+            .write(b"albumart foo/bar.mp3 0\n")
+            .read(b"size: 6\nbinary: 3\nFOO\nOK\n")
+            .write(b"albumart foo/bar.mp3 3\n")
+            .read(b"size: 6\nbinary: 3\nBAR\nOK\n")
+            .build();
+
+        let (client, _) = Client::connect(io).await.expect("connect failed");
+
+        let x = client
+            .album_art("foo/bar.mp3")
+            .await
+            .expect("command failed");
+
+        assert_eq!(x, Some((BytesMut::from("FOOBAR"), None)));
     }
 
     #[tokio::test]
     async fn album_art_fallback_error() {
         let io = MockBuilder::new()
-            .read(GREETING)
+            .read(b"OK MPD 0.23.5\n")
             .write(b"idle\n")
             .write(b"noidle\n")
             .read(b"OK\n")
@@ -898,10 +2850,338 @@ mod tests {
         assert_eq!(x, Some((BytesMut::from("FOOBAR"), None)));
     }
 
+    #[tokio::test]
+    async fn album_art_old_server() {
+        let io = MockBuilder::new()
+            .read(b"OK MPD 0.21.0\n")
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"albumart foo/bar.mp3 0\n")
+            .read(b"size: 3\nbinary: 3\nFOO\nOK\n")
+            .write(b"albumart foo/bar.mp3 0\n")
+            .read(b"size: 3\nbinary: 3\nFOO\nOK\n")
+            .build();
+
+        let (client, _) = Client::connect(io).await.expect("connect failed");
+
+        let x = client
+            .album_art("foo/bar.mp3")
+            .await
+            .expect("command failed");
+
+        assert_eq!(x, Some((BytesMut::from("FOO"), None)));
+
+        let stream = client
+            .album_art_stream("foo/bar.mp3", None)
+            .await
+            .expect("command failed")
+            .expect("no album art");
+
+        assert!(!stream.is_embedded());
+        assert_eq!(stream.size(), 3);
+    }
+
+    #[tokio::test]
+    async fn album_art_stream() {
+        let io = MockBuilder::new()
+            .read(b"OK MPD 0.23.5\n")
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"command_list_ok_begin\nbinarylimit 262144\nreadpicture foo/bar.mp3 0\nbinarylimit 8192\ncommand_list_end\n")
+            .read(b"list_OK\nACK [5@1] {} unknown command \"readpicture\"\n")
+            .write(b"binarylimit 8192\n")
+            .read(b"OK\n")
+            .write(b"command_list_ok_begin\nbinarylimit 262144\nalbumart foo/bar.mp3 0\nbinarylimit 8192\ncommand_list_end\n")
+            .read(b"list_OK\nsize: 6\nbinary: 3\nFOO\nlist_OK\nlist_OK\nOK\n")
+            .write(b"command_list_ok_begin\nbinarylimit 262144\nalbumart foo/bar.mp3 3\nbinarylimit 8192\ncommand_list_end\n")
+            .read(b"list_OK\nsize: 6\nbinary: 3\nBAR\nlist_OK\nlist_OK\nOK\n")
+            .build();
+
+        let (client, _) = Client::connect(io).await.expect("connect failed");
+
+        let mut art = client
+            .album_art_stream("foo/bar.mp3", None)
+            .await
+            .expect("command failed")
+            .expect("no album art");
+
+        assert_eq!(art.size(), 6);
+        assert!(!art.is_embedded());
+        assert_eq!(art.mime(), None);
+
+        assert_eq!(art.next().await.unwrap().unwrap(), "FOO");
+        assert_eq!(art.received(), 3);
+        assert_eq!(art.next().await.unwrap().unwrap(), "BAR");
+        assert_eq!(art.received(), 6);
+        assert!(art.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn album_art_stream_restore_binary_limit() {
+        let io = MockBuilder::new()
+            .read(b"OK MPD 0.23.5\n")
+            .write(b"binarylimit 64\n")
+            .read(b"ACK [2@0] {binarylimit} Value too small\n")
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            // Restores the default limit, since the configured one was rejected
+            .write(b"command_list_ok_begin\nbinarylimit 262144\nreadpicture foo/bar.mp3 0\nbinarylimit 8192\ncommand_list_end\n")
+            .read(b"list_OK\nsize: 3\nbinary: 3\nFOO\nlist_OK\nACK [5@2] {binarylimit} unknown command\n")
+            .write(b"idle\n")
+            .build();
+
+        let config = ClientConfig::new().binary_limit(64);
+        let (client, _) = Client::connect_with_config(io, config)
+            .await
+            .expect("connect failed");
+
+        // Failing to restore the limit does not fail loading the album art
+        let mut stream = client
+            .album_art_stream("foo/bar.mp3", None)
+            .await
+            .expect("command failed")
+            .expect("no album art");
+
+        assert_eq!(stream.next().await.unwrap().unwrap(), Bytes::from("FOO"));
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn album_art_stream_raise_binary_limit_failed() {
+        let io = MockBuilder::new()
+            .read(b"OK MPD 0.23.5\n")
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"command_list_ok_begin\nbinarylimit 262144\nreadpicture foo/bar.mp3 0\nbinarylimit 8192\ncommand_list_end\n")
+            .read(b"ACK [4@0] {binarylimit} Permission denied\n")
+            .write(b"readpicture foo/bar.mp3 0\n")
+            .read(b"size: 3\nbinary: 3\nFOO\nOK\n")
+            .write(b"command_list_ok_begin\nbinarylimit 262144\nreadpicture foo/baz.mp3 0\nbinarylimit 8192\ncommand_list_end\n")
+            .read(b"list_OK\nACK [50@1] {readpicture} No file exists\n")
+            .write(b"binarylimit 8192\n")
+            .read(b"OK\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _) = Client::connect(io).await.expect("connect failed");
+
+        // Failing to raise the limit falls back to loading the album art without it
+        let mut stream = client
+            .album_art_stream("foo/bar.mp3", None)
+            .await
+            .expect("command failed")
+            .expect("no album art");
+
+        assert_eq!(stream.next().await.unwrap().unwrap(), Bytes::from("FOO"));
+        assert!(stream.next().await.is_none());
+
+        let error = client
+            .album_art_stream("foo/baz.mp3", None)
+            .await
+            .unwrap_err();
+
+        assert_matches!(
+            error,
+            CommandError::ErrorResponse { error, succesful_frames }
+                if error.command_index == 0 && succesful_frames.is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn album_art_stream_too_large() {
+        let io = MockBuilder::new()
+            .read(b"OK MPD 0.22.0\n")
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"readpicture foo/bar.mp3 0\n")
+            .read(b"size: 41943040\ntype: image/png\nbinary: 3\nFOO\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _) = Client::connect(io).await.expect("connect failed");
+
+        let error = client
+            .album_art_stream("foo/bar.mp3", Some(4 << 20))
+            .await
+            .unwrap_err();
+
+        assert_matches!(
+            error,
+            CommandError::TooLarge {
+                size: 41943040,
+                max_size: 4194304,
+            }
+        );
+    }
+
+    #[cfg(feature = "cache")]
+    #[tokio::test]
+    async fn album_art_cache() {
+        let io = MockBuilder::new()
+            .read(b"OK MPD 0.23.5\n")
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"readpicture foo/1.mp3 0\n")
+            .read(b"OK\n")
+            .write(b"albumart foo/1.mp3 0\n")
+            .read(b"size: 3\nbinary: 3\nFOO\nOK\n")
+            .write(b"readpicture foo/2.mp3 0\n")
+            .read(b"OK\n")
+            .write(b"readpicture bar/1.mp3 0\n")
+            .read(b"size: 3\ntype: image/png\nbinary: 3\nBAR\nOK\n")
+            .write(b"readpicture foo/1.mp3 0\n")
+            .read(b"OK\n")
+            .write(b"albumart foo/1.mp3 0\n")
+            .read(b"OK\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _) = Client::connect(io).await.expect("connect failed");
+        let cache = AlbumArtCache::new(client);
+
+        let foo = (Bytes::from_static(b"FOO"), None);
+        let bar = (Bytes::from_static(b"BAR"), Some(String::from("image/png")));
+
+        assert_eq!(
+            cache.album_art("foo/1.mp3").await.unwrap(),
+            Some(foo.clone())
+        );
+        // Separate album art files are cached by directory
+        assert_eq!(
+            cache.album_art("foo/2.mp3").await.unwrap(),
+            Some(foo.clone())
+        );
+        assert_eq!(cache.album_art("foo/1.mp3").await.unwrap(), Some(foo));
+
+        assert_eq!(
+            cache.album_art("bar/1.mp3").await.unwrap(),
+            Some(bar.clone())
+        );
+        assert_eq!(cache.album_art("bar/1.mp3").await.unwrap(), Some(bar));
+
+        cache.subsystem_changed(&Subsystem::Player).await;
+        cache.subsystem_changed(&Subsystem::Database).await;
+
+        assert_eq!(cache.album_art("foo/1.mp3").await.unwrap(), None);
+        assert_eq!(cache.album_art("foo/1.mp3").await.unwrap(), None);
+    }
+
+    #[cfg(feature = "cache")]
+    #[tokio::test]
+    async fn album_art_cache_disk() {
+        fn stats(db_update: u64) -> String {
+            format!(
+                "artists: 1\nalbums: 1\nsongs: 1\nuptime: 10\nplaytime: 0\ndb_playtime: 100\n\
+                db_update: {db_update}\nOK\n"
+            )
+        }
+
+        fn mock(db_update: u64, art: Option<&[u8]>) -> tokio_test::io::Mock {
+            let mut io = MockBuilder::new();
+            io.read(b"OK MPD 0.23.5\n")
+                .write(b"idle\n")
+                .write(b"noidle\n")
+                .read(b"OK\n")
+                .write(b"stats\n")
+                .read(stats(db_update).as_bytes());
+
+            if let Some(art) = art {
+                io.write(b"readpicture foo/1.mp3 0\n")
+                    .read(b"size: 3\ntype: image/png\nbinary: 3\n")
+                    .read(art)
+                    .read(b"\nOK\n");
+            }
+
+            io.write(b"idle\n").build()
+        }
+
+        let path = std::env::temp_dir().join(format!("mpd_client_art_{}", std::process::id()));
+
+        let (client, _) = Client::connect(mock(1000, Some(b"FOO")))
+            .await
+            .expect("connect failed");
+        let cache = AlbumArtCache::new(client).disk_cache(&path, "localhost:6600");
+        let art = cache.album_art("foo/1.mp3").await.unwrap();
+
+        // A fresh cache using the same directory only checks for database updates
+        let (client, _) = Client::connect(mock(1000, None))
+            .await
+            .expect("connect failed");
+        let cache = AlbumArtCache::new(client).disk_cache(&path, "localhost:6600");
+
+        assert_eq!(cache.album_art("foo/1.mp3").await.unwrap(), art);
+
+        // The database was updated in the meantime
+        let (client, _) = Client::connect(mock(2000, Some(b"BAR")))
+            .await
+            .expect("connect failed");
+        let cache = AlbumArtCache::new(client).disk_cache(&path, "localhost:6600");
+
+        assert_eq!(
+            cache.album_art("foo/1.mp3").await.unwrap(),
+            Some((Bytes::from_static(b"BAR"), Some(String::from("image/png"))))
+        );
+
+        // Caches of other servers are separate
+        let (client, _) = Client::connect(mock(2000, Some(b"BAZ")))
+            .await
+            .expect("connect failed");
+        let cache = AlbumArtCache::new(client).disk_cache(&path, "otherhost:6600");
+
+        assert_eq!(
+            cache.album_art("foo/1.mp3").await.unwrap(),
+            Some((Bytes::from_static(b"BAZ"), Some(String::from("image/png"))))
+        );
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[cfg(feature = "cache")]
+    #[tokio::test]
+    async fn album_art_cache_disk_capacity() {
+        let path =
+            std::env::temp_dir().join(format!("mpd_client_art_capacity_{}", std::process::id()));
+
+        let io = MockBuilder::new()
+            .read(b"OK MPD 0.23.5\n")
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"stats\n")
+            .read(
+                b"artists: 1\nalbums: 1\nsongs: 1\nuptime: 10\nplaytime: 0\ndb_playtime: 100\n\
+                db_update: 1000\nOK\n",
+            )
+            .write(b"readpicture foo/1.mp3 0\n")
+            .read(b"size: 3\ntype: image/png\nbinary: 3\nFOO\nOK\n")
+            .write(b"readpicture foo/1.mp3 0\n")
+            .read(b"size: 3\ntype: image/png\nbinary: 3\nFOO\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _) = Client::connect(io).await.expect("connect failed");
+        let cache = AlbumArtCache::new(client)
+            .memory_capacity(0)
+            .disk_cache(&path, "localhost:6600")
+            .disk_capacity(8);
+
+        // The album art does not fit into the disk cache, so it is loaded again
+        let art = cache.album_art("foo/1.mp3").await.unwrap();
+        assert_eq!(cache.album_art("foo/1.mp3").await.unwrap(), art);
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
     #[tokio::test]
     async fn album_art_none() {
         let io = MockBuilder::new()
-            .read(GREETING)
+            .read(b"OK MPD 0.23.5\n")
             .write(b"idle\n")
             .write(b"noidle\n")
             .read(b"OK\n")
@@ -921,6 +3201,34 @@ mod tests {
         assert_eq!(x, None);
     }
 
+    #[tokio::test]
+    async fn capabilities() {
+        let io = MockBuilder::new()
+            .read(GREETING)
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"command_list_ok_begin\ncommands\nnotcommands\nurlhandlers\ndecoders\ncommand_list_end\n")
+            .read(b"command: play\ncommand: status\nlist_OK\n")
+            .read(b"command: config\nlist_OK\n")
+            .read(b"handler: http://\nlist_OK\n")
+            .read(b"plugin: mad\nsuffix: mp3\nmime_type: audio/mpeg\nlist_OK\nOK\n")
+            .write(b"config\n")
+            .read(b"ACK [4@0] {config} Permission denied\n")
+            .build();
+
+        let (client, _) = Client::connect(io).await.expect("connect failed");
+
+        let capabilities = client.capabilities().await.expect("command failed");
+
+        assert!(capabilities.has_command("play"));
+        assert!(!capabilities.has_command("config"));
+        assert!(capabilities.not_commands.contains("config"));
+        assert!(capabilities.has_url_handler("http://"));
+        assert_eq!(capabilities.decoders.len(), 1);
+        assert_eq!(capabilities.config, None);
+    }
+
     #[tokio::test]
     async fn protocol_version() {
         let io = MockBuilder::new().read(GREETING).write(b"idle\n").build();
@@ -928,6 +3236,35 @@ mod tests {
         let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
 
         assert_eq!(client.protocol_version(), "0.21.11");
+        assert_eq!(client.version(), Some(ProtocolVersion::new(0, 21, 11)));
+        assert!(client.supports(ProtocolVersion::new(0, 21, 0)));
+        assert!(!client.supports(ProtocolVersion::new(0, 22, 0)));
+    }
+
+    #[tokio::test]
+    async fn unsupported_command() {
+        let io = MockBuilder::new().read(GREETING).write(b"idle\n").build();
+
+        let (client, _state_changes) = Client::connect(io).await.expect("connect failed");
+
+        let error = client
+            .command(cmds::StickerNames)
+            .await
+            .expect_err("command succeeded");
+
+        assert_matches!(
+            error,
+            CommandError::Unsupported { required, server }
+                if required == ProtocolVersion::new(0, 24, 0)
+                    && server == ProtocolVersion::new(0, 21, 11)
+        );
+
+        let error = client
+            .command_list((cmds::Status, cmds::SetBinaryLimit(1024)))
+            .await
+            .expect_err("command list succeeded");
+
+        assert_matches!(error, CommandError::Unsupported { .. });
     }
 
     #[test]
@@ -0,0 +1,503 @@
+use std::{
+    error::Error,
+    fmt, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use bytes::Bytes;
+use image::{DynamicImage, ImageFormat, ImageResult};
+use tracing::{debug, trace, warn};
+
+use super::{
+    AlbumArtCache, CommandError, Subsystem,
+    cache::{CachedArt, Digest, MemoryCache, write_atomic},
+};
+
+/// Default capacity of the in-memory cache, in bytes.
+const DEFAULT_MEMORY_CAPACITY: usize = 8 * 1024 * 1024;
+
+/// Image format of generated thumbnails.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ThumbnailFormat {
+    /// JPEG, without transparency.
+    #[default]
+    Jpeg,
+    /// PNG.
+    Png,
+    /// Lossless WebP.
+    WebP,
+}
+
+impl ThumbnailFormat {
+    /// The MIME type of the format.
+    pub fn mime(&self) -> &'static str {
+        self.image_format().to_mime_type()
+    }
+
+    fn image_format(&self) -> ImageFormat {
+        match self {
+            ThumbnailFormat::Jpeg => ImageFormat::Jpeg,
+            ThumbnailFormat::Png => ImageFormat::Png,
+            ThumbnailFormat::WebP => ImageFormat::WebP,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            ThumbnailFormat::Jpeg => "jpg",
+            ThumbnailFormat::Png => "png",
+            ThumbnailFormat::WebP => "webp",
+        }
+    }
+}
+
+/// Generate thumbnails of the given album art.
+///
+/// The image is decoded using the format given by `mime`, or a format guessed from the data if it
+/// is `None` or unknown. Each thumbnail fits into a square of the given size, preserving the
+/// aspect ratio. Images are never enlarged.
+///
+/// This is CPU intensive, consider running it using [`spawn_blocking`][tokio::task::spawn_blocking].
+///
+/// ```no_run
+/// # async fn example(client: mpd_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+/// use mpd_client::client::{ThumbnailFormat, make_thumbnails};
+///
+/// if let Some((data, mime)) = client.album_art("foo/bar.flac").await? {
+///     let thumbnails = make_thumbnails(&data, mime.as_deref(), &[64, 256], ThumbnailFormat::Png)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// This returns an error if the image can't be decoded or encoded.
+pub fn make_thumbnails(
+    data: &[u8],
+    mime: Option<&str>,
+    sizes: &[u32],
+    format: ThumbnailFormat,
+) -> ImageResult<Vec<Vec<u8>>> {
+    let image = match mime.and_then(ImageFormat::from_mime_type) {
+        Some(source) => image::load_from_memory_with_format(data, source)?,
+        None => image::load_from_memory(data)?,
+    };
+
+    sizes
+        .iter()
+        .map(|&size| encode(&resize(&image, size), format))
+        .collect()
+}
+
+fn resize(image: &DynamicImage, size: u32) -> DynamicImage {
+    if image.width() <= size && image.height() <= size {
+        image.clone()
+    } else {
+        image.thumbnail(size, size)
+    }
+}
+
+fn encode(image: &DynamicImage, format: ThumbnailFormat) -> ImageResult<Vec<u8>> {
+    // Not all encoders support all pixel formats
+    let image = match format {
+        ThumbnailFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8()),
+        ThumbnailFormat::Png => image.clone(),
+        ThumbnailFormat::WebP => DynamicImage::ImageRgba8(image.to_rgba8()),
+    };
+
+    let mut out = io::Cursor::new(Vec::new());
+    image.write_to(&mut out, format.image_format())?;
+    Ok(out.into_inner())
+}
+
+/// Generates and caches thumbnails of album art.
+///
+/// Album art is loaded using an [`AlbumArtCache`], and thumbnails are generated using
+/// [`make_thumbnails`]. They are cached by the digest of the album art they were generated from,
+/// in memory and optionally [on disk][ThumbnailCache::disk_cache].
+///
+/// Like [`AlbumArtCache`], this does not observe changes to the music database by itself, see
+/// [`ThumbnailCache::subsystem_changed`].
+///
+/// ```no_run
+/// # async fn example(client: mpd_client::Client) -> Result<(), mpd_client::client::ThumbnailError> {
+/// use mpd_client::client::{AlbumArtCache, ThumbnailCache, ThumbnailFormat};
+///
+/// let cache = ThumbnailCache::new(AlbumArtCache::new(client))
+///     .format(ThumbnailFormat::WebP)
+///     .disk_cache("/tmp/mpd-thumbnails");
+///
+/// if let Some(thumbnails) = cache.thumbnails("foo/bar.flac", &[64, 128, 512]).await? {
+///     println!("{} bytes", thumbnails[0].len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Cloning the cache is cheap, and the clones share the cached data.
+#[derive(Clone)]
+pub struct ThumbnailCache {
+    album_art: AlbumArtCache,
+    format: ThumbnailFormat,
+    disk: Option<Arc<Path>>,
+    state: Arc<Mutex<State>>,
+}
+
+struct State {
+    /// Incremented on invalidation, to avoid inserting stale entries loaded concurrently.
+    generation: u64,
+    memory: MemoryCache,
+}
+
+impl ThumbnailCache {
+    /// Create a new cache loading album art using the given album art cache.
+    ///
+    /// By default, thumbnails are generated as JPEG, up to 8 MiB of them are cached in memory, and
+    /// nothing is cached on disk.
+    pub fn new(album_art: AlbumArtCache) -> Self {
+        Self {
+            album_art,
+            format: ThumbnailFormat::default(),
+            disk: None,
+            state: Arc::new(Mutex::new(State {
+                generation: 0,
+                memory: MemoryCache::new(DEFAULT_MEMORY_CAPACITY),
+            })),
+        }
+    }
+
+    /// Set the format of generated thumbnails.
+    pub fn format(mut self, format: ThumbnailFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the maximum total size of thumbnails kept in memory, in bytes.
+    pub fn memory_capacity(self, capacity: usize) -> Self {
+        self.state.lock().unwrap().memory.set_capacity(capacity);
+        self
+    }
+
+    /// Additionally cache thumbnails in the given directory.
+    ///
+    /// The directory is created if it does not exist. Since thumbnails are cached by the content
+    /// of the album art, the directory may be shared by caches for different servers. Errors when
+    /// reading or writing the disk cache are logged, but otherwise ignored.
+    pub fn disk_cache(mut self, path: impl Into<PathBuf>) -> Self {
+        self.disk = Some(Arc::from(path.into()));
+        self
+    }
+
+    /// Get a thumbnail of the album art for the given URI, fitting into a square of the given
+    /// size.
+    ///
+    /// # Errors
+    ///
+    /// See [`ThumbnailCache::thumbnails`].
+    pub async fn thumbnail(&self, uri: &str, size: u32) -> Result<Option<Bytes>, ThumbnailError> {
+        let thumbnails = self.thumbnails(uri, &[size]).await?;
+        Ok(thumbnails.map(|mut t| t.remove(0)))
+    }
+
+    /// Get thumbnails of the album art for the given URI in each of the given sizes.
+    ///
+    /// If any of the thumbnails are not cached, the album art is decoded once to generate them.
+    /// Returns `None` if there is no album art.
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if loading the album art fails, or if it can't be decoded.
+    #[tracing::instrument(skip(self))]
+    pub async fn thumbnails(
+        &self,
+        uri: &str,
+        sizes: &[u32],
+    ) -> Result<Option<Vec<Bytes>>, ThumbnailError> {
+        let Some(art) = self.album_art.load(uri).await? else {
+            return Ok(None);
+        };
+
+        let keys = sizes
+            .iter()
+            .map(|&size| self.key(art.digest, size))
+            .collect::<Vec<_>>();
+
+        let mut thumbnails = Vec::with_capacity(sizes.len());
+        let generation = self.state.lock().unwrap().generation;
+
+        for &key in &keys {
+            match self.cached(key).await {
+                Some(thumbnail) => thumbnails.push(thumbnail),
+                None => break,
+            }
+        }
+
+        if thumbnails.len() == sizes.len() {
+            trace!("all thumbnails cached");
+            return Ok(Some(thumbnails));
+        }
+
+        debug!(length = art.data.len(), mime = ?art.mime, "generating thumbnails");
+
+        let format = self.format;
+        let sizes = sizes.to_vec();
+        let generated = match tokio::task::spawn_blocking(move || {
+            make_thumbnails(&art.data, art.mime.as_deref(), &sizes, format)
+        })
+        .await
+        {
+            Ok(generated) => generated?,
+            // Blocking tasks are only cancelled when the runtime shuts down, which also drops this
+            // future, so the task must have panicked
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        };
+
+        let mut thumbnails = Vec::with_capacity(generated.len());
+
+        for (key, thumbnail) in keys.into_iter().zip(generated) {
+            let thumbnail = Bytes::from(thumbnail);
+            self.insert(generation, key, thumbnail.clone()).await;
+            thumbnails.push(thumbnail);
+        }
+
+        Ok(Some(thumbnails))
+    }
+
+    /// Invalidate the album art cache if the given subsystem is the
+    /// [database][Subsystem::Database].
+    ///
+    /// Cached thumbnails remain valid, since they are cached by the content of the album art.
+    pub async fn subsystem_changed(&self, subsystem: &Subsystem) {
+        self.album_art.subsystem_changed(subsystem).await;
+    }
+
+    /// Remove all thumbnails from the cache, including those on disk, and
+    /// [invalidate][AlbumArtCache::invalidate] the album art cache.
+    pub async fn invalidate(&self) {
+        debug!("invalidating thumbnail cache");
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.generation += 1;
+            state.memory.clear();
+        }
+
+        if let Some(disk) = &self.disk {
+            let dir = disk.join("thumbnails");
+
+            match tokio::fs::remove_dir_all(&dir).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => warn!(error = ?e, ?dir, "failed to clear disk cache"),
+            }
+        }
+
+        self.album_art.invalidate().await;
+    }
+
+    fn key(&self, art: Digest, size: u32) -> Digest {
+        Digest::of(format!("{art}\0{size}\0{}", self.format.extension()).as_bytes())
+    }
+
+    fn path(&self, disk: &Path, key: Digest) -> PathBuf {
+        disk.join("thumbnails")
+            .join(format!("{key}.{}", self.format.extension()))
+    }
+
+    async fn cached(&self, key: Digest) -> Option<Bytes> {
+        let generation = {
+            let mut state = self.state.lock().unwrap();
+
+            if let Some(thumbnail) = state.memory.get(key) {
+                return Some(thumbnail.data);
+            }
+
+            state.generation
+        };
+
+        let data = Bytes::from(
+            tokio::fs::read(self.path(self.disk.as_ref()?, key))
+                .await
+                .ok()?,
+        );
+
+        let mut state = self.state.lock().unwrap();
+        if state.generation == generation {
+            state.memory.insert(key, cached(key, data.clone()));
+        }
+
+        Some(data)
+    }
+
+    async fn insert(&self, generation: u64, key: Digest, data: Bytes) {
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.generation != generation {
+                return;
+            }
+
+            state.memory.insert(key, cached(key, data.clone()));
+        }
+
+        if let Some(disk) = &self.disk {
+            let path = self.path(disk, key);
+
+            let result = match tokio::fs::create_dir_all(disk.join("thumbnails")).await {
+                Ok(()) => write_atomic(&path, &data).await,
+                Err(e) => Err(e),
+            };
+
+            if let Err(e) = result {
+                warn!(error = ?e, ?path, "failed to write disk cache");
+            }
+        }
+    }
+}
+
+fn cached(key: Digest, data: Bytes) -> CachedArt {
+    CachedArt {
+        data,
+        mime: None,
+        digest: key,
+    }
+}
+
+impl fmt::Debug for ThumbnailCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThumbnailCache")
+            .field("album_art", &self.album_art)
+            .field("format", &self.format)
+            .field("disk", &self.disk)
+            .field("memory", &self.state.lock().unwrap().memory)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Errors which can occur when generating thumbnails using a [`ThumbnailCache`].
+#[derive(Debug)]
+pub enum ThumbnailError {
+    /// Loading the album art failed.
+    Command(CommandError),
+    /// Decoding the album art or encoding the thumbnail failed.
+    Image(image::ImageError),
+}
+
+impl fmt::Display for ThumbnailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThumbnailError::Command(_) => write!(f, "failed to load album art"),
+            ThumbnailError::Image(_) => write!(f, "failed to generate thumbnail"),
+        }
+    }
+}
+
+impl Error for ThumbnailError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ThumbnailError::Command(e) => Some(e),
+            ThumbnailError::Image(e) => Some(e),
+        }
+    }
+}
+
+#[doc(hidden)]
+impl From<CommandError> for ThumbnailError {
+    fn from(e: CommandError) -> Self {
+        ThumbnailError::Command(e)
+    }
+}
+
+#[doc(hidden)]
+impl From<image::ImageError> for ThumbnailError {
+    fn from(e: image::ImageError) -> Self {
+        ThumbnailError::Image(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{GenericImageView, RgbaImage};
+    use tokio_test::io::Builder as MockBuilder;
+
+    use super::*;
+    use crate::Client;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = DynamicImage::ImageRgba8(RgbaImage::new(width, height));
+        encode(&image, ThumbnailFormat::Png).unwrap()
+    }
+
+    #[test]
+    fn thumbnails() {
+        let data = png(400, 200);
+
+        for format in [
+            ThumbnailFormat::Jpeg,
+            ThumbnailFormat::Png,
+            ThumbnailFormat::WebP,
+        ] {
+            let thumbnails = make_thumbnails(&data, Some("image/png"), &[100, 1000], format)
+                .expect("failed to generate thumbnails");
+
+            let small =
+                image::load_from_memory_with_format(&thumbnails[0], format.image_format()).unwrap();
+            assert_eq!(small.dimensions(), (100, 50));
+
+            let large = image::load_from_memory(&thumbnails[1]).unwrap();
+            assert_eq!(large.dimensions(), (400, 200));
+        }
+
+        // Unknown MIME types fall back to guessing the format
+        let thumbnails =
+            make_thumbnails(&data, Some("image/x-foo"), &[10], ThumbnailFormat::Png).unwrap();
+        assert_eq!(thumbnails.len(), 1);
+
+        assert!(make_thumbnails(b"foo", None, &[10], ThumbnailFormat::Png).is_err());
+    }
+
+    #[tokio::test]
+    async fn thumbnail_cache() {
+        let first = png(64, 64);
+        let second = png(64, 32);
+
+        let io = MockBuilder::new()
+            .read(b"OK MPD 0.23.5\n")
+            .write(b"idle\n")
+            .write(b"noidle\n")
+            .read(b"OK\n")
+            .write(b"readpicture foo/1.mp3 0\n")
+            .read(format!("size: {0}\ntype: image/png\nbinary: {0}\n", first.len()).as_bytes())
+            .read(&first)
+            .read(b"\nOK\n")
+            .write(b"readpicture foo/2.mp3 0\n")
+            .read(format!("size: {0}\ntype: image/png\nbinary: {0}\n", second.len()).as_bytes())
+            .read(&second)
+            .read(b"\nOK\n")
+            .write(b"idle\n")
+            .build();
+
+        let (client, _) = Client::connect(io).await.expect("connect failed");
+        let cache = ThumbnailCache::new(AlbumArtCache::new(client)).format(ThumbnailFormat::Png);
+
+        let thumbnails = cache
+            .thumbnails("foo/1.mp3", &[16, 32])
+            .await
+            .unwrap()
+            .unwrap();
+
+        let small = image::load_from_memory(&thumbnails[0]).unwrap();
+        assert_eq!(small.dimensions(), (16, 16));
+
+        // Songs in the same directory may have different embedded album art
+        let thumbnail = cache.thumbnail("foo/2.mp3", 32).await.unwrap().unwrap();
+        let image = image::load_from_memory(&thumbnail).unwrap();
+        assert_eq!(image.dimensions(), (32, 16));
+
+        // Cached
+        let thumbnail = cache.thumbnail("foo/1.mp3", 32).await.unwrap();
+        assert_eq!(thumbnail.as_ref(), Some(&thumbnails[1]));
+    }
+}
@@ -0,0 +1,142 @@
+use std::{error::Error, fmt, num::ParseIntError, str::FromStr};
+
+/// Version of the MPD protocol, as announced by the server when connecting.
+///
+/// Versions are ordered component-wise, so they can be compared to determine whether a server
+/// supports a particular feature.
+///
+/// ```
+/// use mpd_client::client::ProtocolVersion;
+///
+/// let version: ProtocolVersion = "0.23.5".parse().unwrap();
+///
+/// assert!(version >= ProtocolVersion::new(0, 23, 0));
+/// assert!(version < ProtocolVersion::new(0, 24, 0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProtocolVersion {
+    /// Major version.
+    pub major: u32,
+    /// Minor version.
+    pub minor: u32,
+    /// Patch version.
+    pub patch: u32,
+}
+
+impl ProtocolVersion {
+    /// Create a new version from its components.
+    pub const fn new(major: u32, minor: u32, patch: u32) -> ProtocolVersion {
+        ProtocolVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for ProtocolVersion {
+    type Err = ProtocolVersionError;
+
+    /// Parse a version of the form `major.minor.patch`. The patch component may be omitted, in
+    /// which case it defaults to 0.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('.');
+
+        let mut component = || -> Result<Option<u32>, ProtocolVersionError> {
+            match parts.next() {
+                None => Ok(None),
+                Some(part) => part
+                    .parse()
+                    .map(Some)
+                    .map_err(|e| ProtocolVersionError::new(s).source(e)),
+            }
+        };
+
+        let Some(major) = component()? else {
+            return Err(ProtocolVersionError::new(s));
+        };
+
+        let Some(minor) = component()? else {
+            return Err(ProtocolVersionError::new(s));
+        };
+
+        let patch = component()?.unwrap_or(0);
+
+        if parts.next().is_some() {
+            return Err(ProtocolVersionError::new(s));
+        }
+
+        Ok(ProtocolVersion::new(major, minor, patch))
+    }
+}
+
+/// Error returned when parsing a [`ProtocolVersion`] fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtocolVersionError {
+    raw: Box<str>,
+    source: Option<ParseIntError>,
+}
+
+impl ProtocolVersionError {
+    fn new(raw: &str) -> ProtocolVersionError {
+        ProtocolVersionError {
+            raw: raw.into(),
+            source: None,
+        }
+    }
+
+    fn source(self, source: ParseIntError) -> ProtocolVersionError {
+        ProtocolVersionError {
+            source: Some(source),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ProtocolVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid protocol version {:?}", self.raw)
+    }
+}
+
+impl Error for ProtocolVersionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_ref().map(|e| e as _)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!("0.21.11".parse(), Ok(ProtocolVersion::new(0, 21, 11)));
+        assert_eq!("0.24".parse(), Ok(ProtocolVersion::new(0, 24, 0)));
+
+        assert_matches!("".parse::<ProtocolVersion>(), Err(_));
+        assert_matches!("0".parse::<ProtocolVersion>(), Err(_));
+        assert_matches!("0.23.x".parse::<ProtocolVersion>(), Err(_));
+        assert_matches!("0.23.1.2".parse::<ProtocolVersion>(), Err(_));
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(ProtocolVersion::new(0, 21, 11) < ProtocolVersion::new(0, 22, 0));
+        assert!(ProtocolVersion::new(0, 23, 3) > ProtocolVersion::new(0, 23, 0));
+        assert!(ProtocolVersion::new(1, 0, 0) > ProtocolVersion::new(0, 24, 2));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(ProtocolVersion::new(0, 23, 3).to_string(), "0.23.3");
+    }
+}
@@ -1,6 +1,11 @@
-use mpd_protocol::{command::CommandList as RawCommandList, response::Frame};
+use std::{any::Any, fmt};
 
-use crate::{commands::Command, responses::TypedResponseError};
+use mpd_protocol::{
+    command::{Command as RawCommand, CommandList as RawCommandList},
+    response::Frame,
+};
+
+use crate::{client::ProtocolVersion, commands::Command, responses::TypedResponseError};
 
 /// Types which can be used as a typed command list, using
 /// [`Client::command_list`][crate::Client::command_list].
@@ -15,6 +20,13 @@ pub trait CommandList {
     /// The command list that will be sent, or `None` if no commands.
     fn command_list(&self) -> Option<RawCommandList>;
 
+    /// The minimum protocol version the server must support for all commands in the list.
+    ///
+    /// See [`Command::min_protocol_version`]. The default implementation returns `None`.
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        None
+    }
+
     /// Convert the raw response frames into the proper response types(s).
     ///
     /// # Errors
@@ -38,6 +50,10 @@ where
         Some(raw_commands)
     }
 
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        self.iter().filter_map(Command::min_protocol_version).max()
+    }
+
     fn responses(self, frames: Vec<Frame>) -> Result<Self::Response, TypedResponseError> {
         assert_eq!(self.len(), frames.len());
         let mut out = Vec::with_capacity(self.len());
@@ -50,6 +66,306 @@ where
     }
 }
 
+/// Command lists whose responses can be recovered when one of the commands fails, using
+/// [`Client::command_list_partial`][crate::Client::command_list_partial].
+///
+/// This is implemented for the same types as [`CommandList`].
+pub trait PartialCommandList: CommandList {
+    /// The responses to the commands which completed successfully.
+    ///
+    /// For tuples, this is a tuple of `Option`s which are `None` for commands that did not complete
+    /// successfully. For vectors, this is a vector of the responses to the commands that completed
+    /// successfully, which is shorter than the original list.
+    type PartialResponse;
+
+    /// Convert the raw response frames, which may be fewer than the number of commands in the
+    /// list, into the proper partial response type.
+    ///
+    /// # Errors
+    ///
+    /// This should return an error if any of the responses were invalid.
+    fn partial_responses(
+        self,
+        frames: Vec<Frame>,
+    ) -> Result<Self::PartialResponse, TypedResponseError>;
+}
+
+impl<C> PartialCommandList for Vec<C>
+where
+    C: Command,
+{
+    type PartialResponse = Vec<C::Response>;
+
+    fn partial_responses(
+        self,
+        frames: Vec<Frame>,
+    ) -> Result<Self::PartialResponse, TypedResponseError> {
+        assert!(frames.len() <= self.len());
+        let mut out = Vec::with_capacity(frames.len());
+
+        for (command, frame) in self.into_iter().zip(frames) {
+            out.push(command.response(frame)?);
+        }
+
+        Ok(out)
+    }
+}
+
+/// Command lists of any length with a response for each command, which can be sent in chunks
+/// using [`Client::command_list_chunked`][crate::Client::command_list_chunked].
+///
+/// This is implemented for vectors of the same command type and for [`DynamicCommandList`].
+pub trait ChunkedCommandList: CommandList {
+    /// The response to a single command of the list.
+    type ItemResponse;
+
+    /// Convert the raw response frames, which may be fewer than the number of commands in the
+    /// list, into the responses to the respective commands.
+    ///
+    /// This stops at the first invalid response, returning the responses before it along with
+    /// the error.
+    fn item_responses(
+        self,
+        frames: Vec<Frame>,
+    ) -> (Vec<Self::ItemResponse>, Option<TypedResponseError>);
+}
+
+impl<C> ChunkedCommandList for Vec<C>
+where
+    C: Command,
+{
+    type ItemResponse = C::Response;
+
+    fn item_responses(
+        self,
+        frames: Vec<Frame>,
+    ) -> (Vec<Self::ItemResponse>, Option<TypedResponseError>) {
+        assert!(frames.len() <= self.len());
+        let mut out = Vec::with_capacity(frames.len());
+
+        for (command, frame) in self.into_iter().zip(frames) {
+            match command.response(frame) {
+                Ok(response) => out.push(response),
+                Err(e) => return (out, Some(e)),
+            }
+        }
+
+        (out, None)
+    }
+}
+
+/// A command list of arbitrary, possibly different, commands, which is assembled at runtime.
+///
+/// The responses are returned as type-erased [`AnyResponse`]s in the same order as the commands,
+/// which can be downcast to the response type of the respective command.
+///
+/// ```
+/// use mpd_client::commands::{Add, DynamicCommandList, Play, SongId};
+///
+/// let mut list = DynamicCommandList::new();
+/// list.add(Add::uri("foo.flac"));
+/// list.add(Play::current());
+/// assert_eq!(list.len(), 2);
+///
+/// // after sending the list using `Client::command_list`:
+/// # let responses = vec![mpd_client::commands::AnyResponse::new(SongId(1)), mpd_client::commands::AnyResponse::new(())];
+/// let id: SongId = responses[0].downcast_ref::<SongId>().copied().unwrap();
+/// # assert_eq!(id, SongId(1));
+/// ```
+#[derive(Default)]
+pub struct DynamicCommandList<'a>(Vec<Box<dyn ErasedCommand + Send + 'a>>);
+
+impl<'a> DynamicCommandList<'a> {
+    /// Create an empty command list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a command to the list.
+    pub fn add<C>(&mut self, command: C)
+    where
+        C: Command + Send + 'a,
+        C::Response: Send + 'static,
+    {
+        self.0.push(Box::new(Some(command)));
+    }
+
+    /// Add a command to the list.
+    ///
+    /// Same as [`DynamicCommandList::add`], but takes and returns `self` for chaining.
+    pub fn command<C>(mut self, command: C) -> Self
+    where
+        C: Command + Send + 'a,
+        C::Response: Send + 'static,
+    {
+        self.add(command);
+        self
+    }
+
+    /// Get the number of commands in the list.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the list contains no commands.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Debug for DynamicCommandList<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|c| c.command()))
+            .finish()
+    }
+}
+
+impl CommandList for DynamicCommandList<'_> {
+    type Response = Vec<AnyResponse>;
+
+    fn command_list(&self) -> Option<RawCommandList> {
+        let mut commands = self.0.iter().map(|c| c.command());
+        let mut raw_commands = RawCommandList::new(commands.next()?);
+        raw_commands.extend(commands);
+
+        Some(raw_commands)
+    }
+
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        self.0.iter().filter_map(|c| c.min_protocol_version()).max()
+    }
+
+    fn responses(self, frames: Vec<Frame>) -> Result<Self::Response, TypedResponseError> {
+        assert_eq!(self.0.len(), frames.len());
+        self.partial_responses(frames)
+    }
+}
+
+impl PartialCommandList for DynamicCommandList<'_> {
+    type PartialResponse = Vec<AnyResponse>;
+
+    fn partial_responses(
+        self,
+        frames: Vec<Frame>,
+    ) -> Result<Self::PartialResponse, TypedResponseError> {
+        assert!(frames.len() <= self.0.len());
+        let mut out = Vec::with_capacity(frames.len());
+
+        for (mut command, frame) in self.0.into_iter().zip(frames) {
+            out.push(command.response(frame)?);
+        }
+
+        Ok(out)
+    }
+}
+
+impl ChunkedCommandList for DynamicCommandList<'_> {
+    type ItemResponse = AnyResponse;
+
+    fn item_responses(
+        self,
+        frames: Vec<Frame>,
+    ) -> (Vec<Self::ItemResponse>, Option<TypedResponseError>) {
+        assert!(frames.len() <= self.0.len());
+        let mut out = Vec::with_capacity(frames.len());
+
+        for (mut command, frame) in self.0.into_iter().zip(frames) {
+            match command.response(frame) {
+                Ok(response) => out.push(response),
+                Err(e) => return (out, Some(e)),
+            }
+        }
+
+        (out, None)
+    }
+}
+
+/// Object-safe version of [`Command`].
+///
+/// Implemented for `Option<C>` so that the command can be moved out of a `&mut` reference when
+/// converting the response.
+trait ErasedCommand {
+    fn command(&self) -> RawCommand;
+
+    fn min_protocol_version(&self) -> Option<ProtocolVersion>;
+
+    fn response(&mut self, frame: Frame) -> Result<AnyResponse, TypedResponseError>;
+}
+
+impl<C> ErasedCommand for Option<C>
+where
+    C: Command,
+    C::Response: Send + 'static,
+{
+    fn command(&self) -> RawCommand {
+        self.as_ref().expect("command already consumed").command()
+    }
+
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        self.as_ref()
+            .expect("command already consumed")
+            .min_protocol_version()
+    }
+
+    fn response(&mut self, frame: Frame) -> Result<AnyResponse, TypedResponseError> {
+        let command = self.take().expect("command already consumed");
+        command.response(frame).map(AnyResponse::new)
+    }
+}
+
+/// A type-erased response to a command in a [`DynamicCommandList`].
+pub struct AnyResponse {
+    value: Box<dyn Any + Send>,
+    type_name: &'static str,
+}
+
+impl AnyResponse {
+    /// Wrap the given response.
+    pub fn new<T: Send + 'static>(value: T) -> Self {
+        Self {
+            value: Box::new(value),
+            type_name: std::any::type_name::<T>(),
+        }
+    }
+
+    /// Returns `true` if the response is of type `T`.
+    pub fn is<T: 'static>(&self) -> bool {
+        self.value.is::<T>()
+    }
+
+    /// Get a reference to the response if it is of type `T`.
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        self.value.downcast_ref()
+    }
+
+    /// Convert into the response if it is of type `T`.
+    ///
+    /// # Errors
+    ///
+    /// If the response is of a different type, this returns `self` unchanged.
+    pub fn downcast<T: 'static>(self) -> Result<T, Self> {
+        match self.value.downcast() {
+            Ok(value) => Ok(*value),
+            Err(value) => Err(Self {
+                value,
+                type_name: self.type_name,
+            }),
+        }
+    }
+
+    /// Get the name of the type of the contained response, for diagnostic purposes.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+impl fmt::Debug for AnyResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AnyResponse").field(&self.type_name).finish()
+    }
+}
+
 macro_rules! impl_command_list_tuple {
     ($first_type:ident, $($further_type:ident => $further_idx:tt),*) => {
         impl<$first_type, $($further_type),*> CommandList for ($first_type, $($further_type),*)
@@ -72,6 +388,17 @@ macro_rules! impl_command_list_tuple {
                 Some(commands)
             }
 
+            fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+                #[allow(unused_mut)]
+                let mut version = self.0.min_protocol_version();
+
+                $(
+                    version = version.max(self.$further_idx.min_protocol_version());
+                )*
+
+                version
+            }
+
             fn responses(self, frames: Vec<Frame>) -> Result<Self::Response, TypedResponseError> {
                 let mut frames = frames.into_iter();
 
@@ -83,6 +410,33 @@ macro_rules! impl_command_list_tuple {
                 ))
             }
         }
+
+        impl<$first_type, $($further_type),*> PartialCommandList for ($first_type, $($further_type),*)
+        where
+            $first_type: Command,
+            $(
+                $further_type: Command
+            ),*
+        {
+            type PartialResponse = (
+                Option<$first_type::Response>,
+                $(Option<$further_type::Response>),*
+            );
+
+            fn partial_responses(
+                self,
+                frames: Vec<Frame>,
+            ) -> Result<Self::PartialResponse, TypedResponseError> {
+                let mut frames = frames.into_iter();
+
+                Ok((
+                    frames.next().map(|f| self.0.response(f)).transpose()?,
+                    $(
+                        frames.next().map(|f| self.$further_idx.response(f)).transpose()?,
+                    )*
+                ))
+            }
+        }
     };
 }
 
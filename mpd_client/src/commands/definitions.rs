@@ -14,7 +14,11 @@ use mpd_protocol::{
 };
 
 use crate::{
-    commands::{Command, ReplayGainMode, SeekMode, SingleMode, Song, SongId, SongPosition},
+    client::ProtocolVersion,
+    commands::{
+        Command, ConsumeMode, ReplayGainMode, SeekMode, SingleMode, Song, SongId, SongPosition,
+        StickerSort,
+    },
     filter::Filter,
     responses::{self as res, TypedResponseError, value},
     tag::Tag,
@@ -87,12 +91,12 @@ argless_command!(Stop, "stop");
 single_arg_command!(ClearPlaylist<'a>, &'a str, "playlistclear");
 single_arg_command!(DeletePlaylist<'a>, &'a str, "rm");
 single_arg_command!(SaveQueueAsPlaylist<'a>, &'a str, "save");
-single_arg_command!(SetConsume, bool, "consume");
 single_arg_command!(SetPause, bool, "pause");
 single_arg_command!(SetRandom, bool, "random");
 single_arg_command!(SetRepeat, bool, "repeat");
 single_arg_command!(SubscribeToChannel<'a>, &'a str, "subscribe");
 single_arg_command!(UnsubscribeFromChannel<'a>, &'a str, "unsubscribe");
+single_arg_command!(Unmount<'a>, &'a str, "unmount");
 
 /// `replay_gain_status` command.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -380,6 +384,77 @@ impl Command for SetVolume {
     }
 }
 
+/// `volume` command.
+///
+/// Change the volume relative to the current value. The value is clamped to the range
+/// `-100..=100`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChangeVolume(pub i8);
+
+impl Command for ChangeVolume {
+    type Response = ();
+
+    fn command(&self) -> RawCommand {
+        let change = self.0.clamp(-100, 100);
+        RawCommand::new("volume").argument(change.to_string())
+    }
+
+    fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(())
+    }
+}
+
+/// `getvol` command.
+///
+/// Returns `None` if the server has no mixer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GetVolume;
+
+impl Command for GetVolume {
+    type Response = Option<u8>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("getvol")
+    }
+
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        Some(ProtocolVersion::new(0, 23, 0))
+    }
+
+    fn response(self, mut frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        res::parse_volume(&mut frame)
+    }
+}
+
+/// `consume` command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SetConsume(pub ConsumeMode);
+
+impl Command for SetConsume {
+    type Response = ();
+
+    fn command(&self) -> RawCommand {
+        let consume = match self.0 {
+            ConsumeMode::Disabled => "0",
+            ConsumeMode::Enabled => "1",
+            ConsumeMode::Oneshot => "oneshot",
+        };
+
+        RawCommand::new("consume").argument(consume)
+    }
+
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        match self.0 {
+            ConsumeMode::Oneshot => Some(ProtocolVersion::new(0, 24, 0)),
+            _ => None,
+        }
+    }
+
+    fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(())
+    }
+}
+
 /// `single` command.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct SetSingle(pub SingleMode);
@@ -444,6 +519,54 @@ impl Command for Crossfade {
     }
 }
 
+/// `mixrampdb` command.
+///
+/// Set the volume threshold in decibels at which songs overlap when using MixRamp.
+///
+/// # Panics
+///
+/// This will panic when sending the command if the value is not finite, since MPD rejects `NaN`
+/// and infinite values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MixRampDb(pub f32);
+
+impl Command for MixRampDb {
+    type Response = ();
+
+    fn command(&self) -> RawCommand {
+        assert!(self.0.is_finite(), "MixRamp threshold must be finite");
+        RawCommand::new("mixrampdb").argument(self.0.to_string())
+    }
+
+    fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(())
+    }
+}
+
+/// `mixrampdelay` command.
+///
+/// Set the additional time subtracted from the overlap calculated by MixRamp, or disable MixRamp
+/// and use crossfading instead if `None`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MixRampDelay(pub Option<Duration>);
+
+impl Command for MixRampDelay {
+    type Response = ();
+
+    fn command(&self) -> RawCommand {
+        let command = RawCommand::new("mixrampdelay");
+
+        match self.0 {
+            Some(delay) => command.argument(delay),
+            None => command.argument("nan"),
+        }
+    }
+
+    fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(())
+    }
+}
+
 /// `seek` and `seekid` commands.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct SeekTo(pub Song, pub Duration);
@@ -638,6 +761,15 @@ impl Command for Add<'_> {
         command
     }
 
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        match self.position {
+            Some(PositionOrRelative::BeforeCurrent(_) | PositionOrRelative::AfterCurrent(_)) => {
+                Some(ProtocolVersion::new(0, 23, 0))
+            }
+            _ => None,
+        }
+    }
+
     fn response(self, mut frame: Frame) -> Result<Self::Response, TypedResponseError> {
         value(&mut frame, "Id").map(SongId)
     }
@@ -1102,6 +1234,10 @@ impl Command for AddToPlaylist<'_> {
         command
     }
 
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        self.position.map(|_| ProtocolVersion::new(0, 23, 3))
+    }
+
     fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
         Ok(())
     }
@@ -1228,6 +1364,8 @@ impl Command for ListAllIn<'_> {
 ///
 /// This can dramatically speed up operations like [loading album art][crate::Client::album_art],
 /// but may cause undesirable latency when using MPD over a slow connection.
+///
+/// **NOTE**: Supported on protocol versions later than 0.22.4.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct SetBinaryLimit(pub usize);
 
@@ -1238,6 +1376,10 @@ impl Command for SetBinaryLimit {
         RawCommand::new("binarylimit").argument(self.0)
     }
 
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        Some(ProtocolVersion::new(0, 22, 4))
+    }
+
     fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
         Ok(())
     }
@@ -1304,6 +1446,10 @@ impl Command for AlbumArtEmbedded<'_> {
             .argument(self.offset)
     }
 
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        Some(ProtocolVersion::new(0, 22, 0))
+    }
+
     fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
         res::AlbumArt::from_frame(frame)
     }
@@ -1343,6 +1489,19 @@ impl<'a> TagTypes<'a> {
         assert_ne!(tags.len(), 0, "The list of tags must not be empty");
         TagTypes(TagTypesAction::Enable(tags))
     }
+
+    /// Enable exactly the given list of tags, disabling all others.
+    ///
+    /// Unlike a combination of [`disable_all`][TagTypes::disable_all] and
+    /// [`enable`][TagTypes::enable], this is a single command.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called with an empty list of tags.
+    pub fn reset(tags: &'a [Tag]) -> TagTypes<'a> {
+        assert_ne!(tags.len(), 0, "The list of tags must not be empty");
+        TagTypes(TagTypesAction::Reset(tags))
+    }
 }
 
 impl Command for TagTypes<'_> {
@@ -1364,6 +1523,13 @@ impl Command for TagTypes<'_> {
             TagTypesAction::Enable(tags) => {
                 cmd.add_argument("enable").unwrap();
 
+                for tag in tags.iter() {
+                    cmd.add_argument(tag).unwrap();
+                }
+            }
+            TagTypesAction::Reset(tags) => {
+                cmd.add_argument("reset").unwrap();
+
                 for tag in tags.iter() {
                     cmd.add_argument(tag).unwrap();
                 }
@@ -1373,6 +1539,13 @@ impl Command for TagTypes<'_> {
         cmd
     }
 
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        match self.0 {
+            TagTypesAction::Reset(_) => Some(ProtocolVersion::new(0, 24, 0)),
+            _ => None,
+        }
+    }
+
     fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
         Ok(())
     }
@@ -1384,6 +1557,7 @@ enum TagTypesAction<'a> {
     Clear,
     Disable(&'a [Tag]),
     Enable(&'a [Tag]),
+    Reset(&'a [Tag]),
 }
 
 /// `sticker get` command
@@ -1524,6 +1698,8 @@ pub struct StickerFind<'a> {
     uri: &'a str,
     name: &'a str,
     filter: Option<(StickerFindOperator, &'a str)>,
+    sort: Option<(StickerSort, bool)>,
+    window: Option<SongRange>,
 }
 
 impl<'a> StickerFind<'a> {
@@ -1533,6 +1709,8 @@ impl<'a> StickerFind<'a> {
             uri,
             name,
             filter: None,
+            sort: None,
+            window: None,
         }
     }
 
@@ -1551,11 +1729,41 @@ impl<'a> StickerFind<'a> {
         self.add_filter(StickerFindOperator::LessThan, value)
     }
 
+    /// Sort the result in ascending order.
+    ///
+    /// The sorted order is available through [`StickerFind::entries`][entries] on the response.
+    ///
+    /// **NOTE**: Supported on protocol versions later than 0.24.
+    ///
+    /// [entries]: crate::responses::StickerFind::entries
+    pub fn sort(mut self, sort_by: StickerSort) -> Self {
+        self.sort = Some((sort_by, false));
+        self
+    }
+
+    /// Sort the result in descending order.
+    ///
+    /// **NOTE**: Supported on protocol versions later than 0.24.
+    pub fn sort_descending(mut self, sort_by: StickerSort) -> Self {
+        self.sort = Some((sort_by, true));
+        self
+    }
+
+    /// Limit the result to the given window.
+    ///
+    /// **NOTE**: Supported on protocol versions later than 0.24.
+    pub fn window<R>(mut self, window: R) -> Self
+    where
+        R: RangeBounds<usize>,
+    {
+        self.window = Some(SongRange::new_usize(window));
+        self
+    }
+
     fn add_filter(self, operator: StickerFindOperator, value: &'a str) -> Self {
         Self {
-            name: self.name,
-            uri: self.uri,
             filter: Some((operator, value)),
+            ..self
         }
     }
 }
@@ -1564,20 +1772,49 @@ impl Command for StickerFind<'_> {
     type Response = res::StickerFind;
 
     fn command(&self) -> RawCommand {
-        let base = RawCommand::new("sticker")
+        let mut command = RawCommand::new("sticker")
             .argument("find")
             .argument("song")
             .argument(self.uri)
             .argument(self.name);
 
         if let Some((operator, value)) = self.filter.as_ref() {
-            match operator {
-                StickerFindOperator::Equals => base.argument("=").argument(value),
-                StickerFindOperator::GreaterThan => base.argument(">").argument(value),
-                StickerFindOperator::LessThan => base.argument("<").argument(value),
+            command = match operator {
+                StickerFindOperator::Equals => command.argument("=").argument(value),
+                StickerFindOperator::GreaterThan => command.argument(">").argument(value),
+                StickerFindOperator::LessThan => command.argument("<").argument(value),
+            };
+        }
+
+        if let Some((sort, descending)) = self.sort {
+            let sort = match sort {
+                StickerSort::Uri => "uri",
+                StickerSort::Value => "value",
+                StickerSort::ValueNumeric => "value_int",
+            };
+
+            command.add_argument("sort").unwrap();
+
+            if descending {
+                command.add_argument(format!("-{sort}")).unwrap();
+            } else {
+                command.add_argument(sort).unwrap();
             }
+        }
+
+        if let Some(window) = self.window {
+            command.add_argument("window").unwrap();
+            command.add_argument(window).unwrap();
+        }
+
+        command
+    }
+
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        if self.sort.is_some() || self.window.is_some() {
+            Some(ProtocolVersion::new(0, 24, 0))
         } else {
-            base
+            None
         }
     }
 
@@ -1586,6 +1823,175 @@ impl Command for StickerFind<'_> {
     }
 }
 
+/// `sticker inc` command
+///
+/// **NOTE**: Supported on protocol versions later than 0.24.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StickerInc<'a> {
+    uri: &'a str,
+    name: &'a str,
+    delta: u64,
+}
+
+impl<'a> StickerInc<'a> {
+    /// Increment the sticker `name` for the song at `uri` by `delta`.
+    ///
+    /// If the sticker does not exist yet, it is created with the value `delta`.
+    pub fn new(uri: &'a str, name: &'a str, delta: u64) -> Self {
+        Self { uri, name, delta }
+    }
+}
+
+impl Command for StickerInc<'_> {
+    type Response = ();
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("sticker")
+            .argument("inc")
+            .argument("song")
+            .argument(self.uri)
+            .argument(self.name)
+            .argument(self.delta)
+    }
+
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        Some(ProtocolVersion::new(0, 24, 0))
+    }
+
+    fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(())
+    }
+}
+
+/// `sticker dec` command
+///
+/// **NOTE**: Supported on protocol versions later than 0.24.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StickerDec<'a> {
+    uri: &'a str,
+    name: &'a str,
+    delta: u64,
+}
+
+impl<'a> StickerDec<'a> {
+    /// Decrement the sticker `name` for the song at `uri` by `delta`.
+    pub fn new(uri: &'a str, name: &'a str, delta: u64) -> Self {
+        Self { uri, name, delta }
+    }
+}
+
+impl Command for StickerDec<'_> {
+    type Response = ();
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("sticker")
+            .argument("dec")
+            .argument("song")
+            .argument(self.uri)
+            .argument(self.name)
+            .argument(self.delta)
+    }
+
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        Some(ProtocolVersion::new(0, 24, 0))
+    }
+
+    fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(())
+    }
+}
+
+/// `stickernames` command.
+///
+/// Lists the distinct names of all stickers in the database.
+///
+/// **NOTE**: Supported on protocol versions later than 0.24.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StickerNames;
+
+impl Command for StickerNames {
+    type Response = Vec<String>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("stickernames")
+    }
+
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        Some(ProtocolVersion::new(0, 24, 0))
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        res::parse_single_field_list(frame, "name")
+    }
+}
+
+/// `stickertypes` command.
+///
+/// Lists the object types stickers can be attached to.
+///
+/// **NOTE**: Supported on protocol versions later than 0.24.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StickerTypes;
+
+impl Command for StickerTypes {
+    type Response = Vec<String>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("stickertypes")
+    }
+
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        Some(ProtocolVersion::new(0, 24, 0))
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        res::parse_single_field_list(frame, "stickertype")
+    }
+}
+
+/// `stickernamestypes` command.
+///
+/// Lists the distinct sticker names together with the object type they are attached to, as
+/// `(name, type)` pairs.
+///
+/// **NOTE**: Supported on protocol versions later than 0.24.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StickerNamesTypes<'a>(Option<&'a str>);
+
+impl<'a> StickerNamesTypes<'a> {
+    /// List the sticker names of all object types.
+    pub fn new() -> Self {
+        StickerNamesTypes(None)
+    }
+
+    /// Restrict the result to stickers on the given object type (e.g. `song`).
+    pub fn sticker_type(self, sticker_type: &'a str) -> Self {
+        Self(Some(sticker_type))
+    }
+}
+
+impl Command for StickerNamesTypes<'_> {
+    type Response = Vec<(String, String)>;
+
+    fn command(&self) -> RawCommand {
+        let mut command = RawCommand::new("stickernamestypes");
+
+        if let Some(sticker_type) = self.0 {
+            command.add_argument(sticker_type).unwrap();
+        }
+
+        command
+    }
+
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        Some(ProtocolVersion::new(0, 24, 0))
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        res::parse_sticker_names_types(frame)
+    }
+}
+
 /// `update` command.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Update<'a>(Option<&'a str>);
@@ -1697,16 +2103,7 @@ impl Command for ListChannels {
     }
 
     fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
-        let mut response = Vec::with_capacity(frame.fields_len());
-        for (key, value) in frame {
-            if &*key != "channel" {
-                return Err(TypedResponseError::unexpected_field("channel", &*key));
-            }
-
-            response.push(value);
-        }
-
-        Ok(response)
+        res::parse_single_field_list(frame, "channel")
     }
 }
 
@@ -1738,6 +2135,213 @@ impl Command for SendChannelMessage<'_> {
     }
 }
 
+/// `mount` command.
+///
+/// Mounts a storage on the given path in the music directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mount<'a> {
+    path: &'a str,
+    uri: &'a str,
+}
+
+impl<'a> Mount<'a> {
+    /// Mount the storage at `uri` (e.g. `nfs://...` or `smb://...`) on `path`.
+    pub fn new(path: &'a str, uri: &'a str) -> Self {
+        Self { path, uri }
+    }
+}
+
+impl Command for Mount<'_> {
+    type Response = ();
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("mount")
+            .argument(self.path)
+            .argument(self.uri)
+    }
+
+    fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(())
+    }
+}
+
+/// `listmounts` command.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ListMounts;
+
+impl Command for ListMounts {
+    type Response = Vec<res::Mount>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("listmounts")
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        res::Mount::parse_frame(frame)
+    }
+}
+
+/// `listneighbors` command.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ListNeighbors;
+
+impl Command for ListNeighbors {
+    type Response = Vec<res::Neighbor>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("listneighbors")
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        res::Neighbor::parse_frame(frame)
+    }
+}
+
+/// `commands` command.
+///
+/// Lists the commands the current client has access to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Commands;
+
+impl Command for Commands {
+    type Response = Vec<String>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("commands")
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        res::parse_single_field_list(frame, "command")
+    }
+}
+
+/// `notcommands` command.
+///
+/// Lists the commands the current client does not have access to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NotCommands;
+
+impl Command for NotCommands {
+    type Response = Vec<String>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("notcommands")
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        res::parse_single_field_list(frame, "command")
+    }
+}
+
+/// `urlhandlers` command.
+///
+/// Lists the URL schemes the server supports (e.g. `http://`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UrlHandlers;
+
+impl Command for UrlHandlers {
+    type Response = Vec<String>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("urlhandlers")
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        res::parse_single_field_list(frame, "handler")
+    }
+}
+
+/// `decoders` command.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Decoders;
+
+impl Command for Decoders {
+    type Response = Vec<res::Decoder>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("decoders")
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        res::Decoder::parse_frame(frame)
+    }
+}
+
+/// `outputs` command.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Outputs;
+
+impl Command for Outputs {
+    type Response = Vec<res::Output>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("outputs")
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        res::Output::parse_frame(frame)
+    }
+}
+
+/// `getfingerprint` command.
+///
+/// Calculate the [Chromaprint](https://acoustid.org/chromaprint) fingerprint of the song at the
+/// given URI. This decodes the song, so it may take a while.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GetFingerprint<'a>(pub &'a str);
+
+impl Command for GetFingerprint<'_> {
+    type Response = String;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("getfingerprint").argument(self.0)
+    }
+
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        Some(ProtocolVersion::new(0, 22, 0))
+    }
+
+    fn response(self, mut frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        value(&mut frame, "chromaprint")
+    }
+}
+
+/// `readcomments` command.
+///
+/// Read all comments (tags) of the song file at the given URI, including those not known to MPD.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReadComments<'a>(pub &'a str);
+
+impl Command for ReadComments<'_> {
+    type Response = res::Comments;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("readcomments").argument(self.0)
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(res::Comments::from_frame(frame))
+    }
+}
+
+/// `config` command.
+///
+/// This is only permitted for clients connected through a local (Unix) socket.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Config;
+
+impl Command for Config {
+    type Response = res::Config;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("config")
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        res::Config::from_frame(frame)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1820,6 +2424,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn command_change_volume() {
+        assert_eq!(
+            ChangeVolume(-10).command(),
+            RawCommand::new("volume").argument("-10")
+        );
+        assert_eq!(
+            ChangeVolume(127).command(),
+            RawCommand::new("volume").argument("100")
+        );
+        assert_eq!(GetVolume.command(), RawCommand::new("getvol"));
+    }
+
+    #[test]
+    fn command_consume() {
+        assert_eq!(
+            SetConsume(ConsumeMode::Enabled).command(),
+            RawCommand::new("consume").argument("1")
+        );
+        assert_eq!(
+            SetConsume(false.into()).command(),
+            RawCommand::new("consume").argument("0")
+        );
+        assert_eq!(
+            SetConsume(ConsumeMode::Oneshot).command(),
+            RawCommand::new("consume").argument("oneshot")
+        );
+    }
+
+    #[test]
+    fn command_mixramp() {
+        assert_eq!(
+            MixRampDb(-17.5).command(),
+            RawCommand::new("mixrampdb").argument("-17.5")
+        );
+        assert_eq!(
+            MixRampDelay(Some(Duration::from_millis(1500))).command(),
+            RawCommand::new("mixrampdelay").argument("1.500")
+        );
+        assert_eq!(
+            MixRampDelay(None).command(),
+            RawCommand::new("mixrampdelay").argument("nan")
+        );
+    }
+
+    #[test]
+    #[should_panic = "MixRamp threshold must be finite"]
+    fn command_mixramp_db_nan() {
+        MixRampDb(f32::NAN).command();
+    }
+
     #[test]
     fn command_seek_to() {
         let duration = Duration::from_secs(2);
@@ -2069,6 +2724,19 @@ mod tests {
                 .argument("Album")
                 .argument("Title")
         );
+
+        let reset = TagTypes::reset(&[Tag::Album, Tag::Title]);
+        assert_eq!(
+            reset.command(),
+            RawCommand::new("tagtypes")
+                .argument("reset")
+                .argument("Album")
+                .argument("Title")
+        );
+        assert_eq!(
+            reset.min_protocol_version(),
+            Some(ProtocolVersion::new(0, 24, 0))
+        );
     }
 
     #[test]
@@ -2147,6 +2815,78 @@ mod tests {
         );
     }
 
+    #[test]
+    fn command_sticker_find_sorted() {
+        assert_eq!(
+            StickerFind::new("foo", "rating")
+                .sort_descending(StickerSort::ValueNumeric)
+                .window(..50)
+                .command(),
+            RawCommand::new("sticker")
+                .argument("find")
+                .argument("song")
+                .argument("foo")
+                .argument("rating")
+                .argument("sort")
+                .argument("-value_int")
+                .argument("window")
+                .argument("0:50")
+        );
+
+        assert_eq!(
+            StickerFind::new("foo", "bar")
+                .where_gt("3")
+                .sort(StickerSort::Uri)
+                .command(),
+            RawCommand::new("sticker")
+                .argument("find")
+                .argument("song")
+                .argument("foo")
+                .argument("bar")
+                .argument(">")
+                .argument("3")
+                .argument("sort")
+                .argument("uri")
+        );
+    }
+
+    #[test]
+    fn command_sticker_inc_dec() {
+        assert_eq!(
+            StickerInc::new("foo", "playcount", 1).command(),
+            RawCommand::new("sticker")
+                .argument("inc")
+                .argument("song")
+                .argument("foo")
+                .argument("playcount")
+                .argument("1")
+        );
+
+        assert_eq!(
+            StickerDec::new("foo", "playcount", 2).command(),
+            RawCommand::new("sticker")
+                .argument("dec")
+                .argument("song")
+                .argument("foo")
+                .argument("playcount")
+                .argument("2")
+        );
+    }
+
+    #[test]
+    fn command_sticker_names_types() {
+        assert_eq!(StickerNames.command(), RawCommand::new("stickernames"));
+        assert_eq!(StickerTypes.command(), RawCommand::new("stickertypes"));
+        assert_eq!(
+            StickerNamesTypes::new().command(),
+            RawCommand::new("stickernamestypes")
+        );
+        assert_eq!(
+            StickerNamesTypes::new().sticker_type("song").command(),
+            RawCommand::new("stickernamestypes").argument("song")
+        );
+    }
+
     #[test]
     fn command_update() {
         assert_eq!(Update::new().command(), RawCommand::new("update"));
@@ -2205,4 +2945,71 @@ mod tests {
                 .argument("bar")
         );
     }
+
+    #[test]
+    fn command_mounts() {
+        assert_eq!(
+            Mount::new("nas", "nfs://192.168.1.4/export/mp3").command(),
+            RawCommand::new("mount")
+                .argument("nas")
+                .argument("nfs://192.168.1.4/export/mp3")
+        );
+        assert_eq!(
+            Unmount("nas").command(),
+            RawCommand::new("unmount").argument("nas")
+        );
+        assert_eq!(ListMounts.command(), RawCommand::new("listmounts"));
+        assert_eq!(ListNeighbors.command(), RawCommand::new("listneighbors"));
+    }
+
+    #[test]
+    fn command_fingerprint_comments() {
+        assert_eq!(
+            GetFingerprint("foo.flac").command(),
+            RawCommand::new("getfingerprint").argument("foo.flac")
+        );
+        assert_eq!(
+            ReadComments("foo.flac").command(),
+            RawCommand::new("readcomments").argument("foo.flac")
+        );
+    }
+
+    #[test]
+    fn command_introspection() {
+        assert_eq!(Commands.command(), RawCommand::new("commands"));
+        assert_eq!(NotCommands.command(), RawCommand::new("notcommands"));
+        assert_eq!(UrlHandlers.command(), RawCommand::new("urlhandlers"));
+        assert_eq!(Decoders.command(), RawCommand::new("decoders"));
+        assert_eq!(Config.command(), RawCommand::new("config"));
+        assert_eq!(Outputs.command(), RawCommand::new("outputs"));
+    }
+
+    #[test]
+    fn min_protocol_version() {
+        assert_eq!(Add::uri("foo").min_protocol_version(), None);
+        assert_eq!(
+            GetFingerprint("foo").min_protocol_version(),
+            Some(ProtocolVersion::new(0, 22, 0))
+        );
+        assert_eq!(
+            SetConsume(ConsumeMode::Enabled).min_protocol_version(),
+            None
+        );
+        assert_eq!(
+            SetConsume(ConsumeMode::Oneshot).min_protocol_version(),
+            Some(ProtocolVersion::new(0, 24, 0))
+        );
+        assert_eq!(
+            Add::uri("foo").after_current(0).min_protocol_version(),
+            Some(ProtocolVersion::new(0, 23, 0))
+        );
+
+        assert_eq!(StickerFind::new("foo", "bar").min_protocol_version(), None);
+        assert_eq!(
+            StickerFind::new("foo", "bar")
+                .window(..10)
+                .min_protocol_version(),
+            Some(ProtocolVersion::new(0, 24, 0))
+        );
+    }
 }
@@ -20,11 +20,19 @@ use mpd_protocol::{
     response::Frame,
 };
 
-pub use self::{command_list::CommandList, definitions::*};
-use crate::responses::TypedResponseError;
+pub use self::{
+    command_list::{
+        AnyResponse, ChunkedCommandList, CommandList, DynamicCommandList, PartialCommandList,
+    },
+    definitions::*,
+};
+use crate::{client::ProtocolVersion, responses::TypedResponseError};
+#[cfg(feature = "derive")]
+pub use mpd_client_derive::MpdCommand;
 
 /// Stable identifier of a song in the queue.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SongId(pub u64);
 
 impl From<u64> for SongId {
@@ -43,6 +51,7 @@ impl Argument for SongId {
 ///
 /// This will change when the queue is modified.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SongPosition(pub usize);
 
 impl From<usize> for SongPosition {
@@ -70,6 +79,7 @@ pub enum SeekMode {
 
 /// Possible `single` modes.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum SingleMode {
     Enabled,
@@ -77,8 +87,30 @@ pub enum SingleMode {
     Oneshot,
 }
 
+/// Possible `consume` modes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum ConsumeMode {
+    Enabled,
+    Disabled,
+    /// Only supported by protocol versions 0.24 and later.
+    Oneshot,
+}
+
+impl From<bool> for ConsumeMode {
+    fn from(enabled: bool) -> Self {
+        if enabled {
+            Self::Enabled
+        } else {
+            Self::Disabled
+        }
+    }
+}
+
 /// Possible `replay_gain_mode` modes.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum ReplayGainMode {
     /// Replay Gain off
@@ -91,6 +123,17 @@ pub enum ReplayGainMode {
     Auto,
 }
 
+/// Possible ways to sort the results of a [`sticker find`][crate::commands::StickerFind] command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StickerSort {
+    /// By the URI of the object the sticker is attached to.
+    Uri,
+    /// By the sticker value, compared as strings.
+    Value,
+    /// By the sticker value, compared as integers.
+    ValueNumeric,
+}
+
 /// Modes to target a song with a command.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Song {
@@ -120,6 +163,17 @@ pub trait Command {
     /// Create the raw command representation for transmission.
     fn command(&self) -> RawCommand;
 
+    /// The minimum protocol version the server must support for this command (with its current
+    /// arguments) to be accepted.
+    ///
+    /// [`Client::command`][crate::Client::command] uses this to return an
+    /// [`Unsupported`][crate::client::CommandError::Unsupported] error without sending the command
+    /// when connected to an older server. The default implementation returns `None`, meaning no
+    /// particular version is required.
+    fn min_protocol_version(&self) -> Option<ProtocolVersion> {
+        None
+    }
+
     /// Convert the raw response frame to the proper response type.
     ///
     /// # Errors
@@ -14,6 +14,9 @@ const TAG_IS_ABSENT: &str = "";
 
 /// A [filter expression].
 ///
+/// With the `serde` feature, filters are serialized as the filter expression parsed by MPD, such as
+/// `(Artist == "foo")`.
+///
 /// [filter expression]: https://www.musicpd.org/doc/html/protocol.html#filters
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Filter(FilterType);
@@ -107,6 +110,28 @@ impl Argument for Filter {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Filter {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut expression = String::new();
+        self.0.write_expression(&mut expression);
+        serializer.serialize_str(&expression)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Filter {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        let mut parser = Parser(&raw);
+
+        match parser.expression() {
+            Some(filter) if parser.0.is_empty() => Ok(Filter(filter)),
+            _ => Err(serde::de::Error::custom("invalid filter expression")),
+        }
+    }
+}
+
 impl Not for Filter {
     type Output = Self;
 
@@ -193,8 +218,126 @@ impl FilterType {
     }
 }
 
+#[cfg(feature = "serde")]
+impl FilterType {
+    /// Write the filter expression as parsed by MPD, without the quoting of a command argument.
+    fn write_expression(&self, out: &mut String) {
+        match self {
+            FilterType::Tag {
+                tag,
+                operator,
+                value,
+            } => {
+                write!(out, "({} {} \"", tag.as_str(), operator.as_str()).unwrap();
+
+                for c in value.chars() {
+                    if c == '"' || c == '\\' {
+                        out.push('\\');
+                    }
+
+                    out.push(c);
+                }
+
+                out.push_str("\")");
+            }
+            FilterType::Not(inner) => {
+                out.push_str("(!");
+                inner.write_expression(out);
+                out.push(')');
+            }
+            FilterType::And(inner) => {
+                out.push('(');
+
+                for (i, filter) in inner.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(" AND ");
+                    }
+
+                    filter.write_expression(out);
+                }
+
+                out.push(')');
+            }
+        }
+    }
+}
+
+/// Parser for the filter expressions written by [`FilterType::write_expression`].
+#[cfg(feature = "serde")]
+struct Parser<'a>(&'a str);
+
+#[cfg(feature = "serde")]
+impl Parser<'_> {
+    fn eat(&mut self, token: &str) -> bool {
+        match self.0.strip_prefix(token) {
+            Some(rest) => {
+                self.0 = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn word(&mut self) -> Option<&str> {
+        let (word, rest) = self.0.split_once(' ')?;
+        self.0 = rest;
+        Some(word)
+    }
+
+    fn expression(&mut self) -> Option<FilterType> {
+        if !self.eat("(") {
+            return None;
+        }
+
+        if self.eat("!") {
+            let inner = self.expression()?;
+            return self.eat(")").then(|| FilterType::Not(Box::new(inner)));
+        }
+
+        if self.0.starts_with('(') {
+            let mut inner = vec![self.expression()?];
+
+            while self.eat(" AND ") {
+                inner.push(self.expression()?);
+            }
+
+            return (inner.len() >= 2 && self.eat(")")).then_some(FilterType::And(inner));
+        }
+
+        let tag = Tag::try_from(self.word()?).ok()?;
+        let operator = Operator::parse(self.word()?)?;
+        let value = self.string()?;
+
+        self.eat(")").then_some(FilterType::Tag {
+            tag,
+            operator,
+            value,
+        })
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let quoted = self.0.strip_prefix('"')?;
+        let mut chars = quoted.char_indices();
+        let mut value = String::new();
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.0 = &quoted[i + 1..];
+                    return Some(value);
+                }
+                '\\' => value.push(chars.next()?.1),
+                c => value.push(c),
+            }
+        }
+
+        None
+    }
+}
+
 /// Operators which can be used in filter expressions.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Operator {
     /// Equality (`==`)
     Equal,
@@ -218,6 +361,18 @@ impl Operator {
             Operator::NotMatch => "!~",
         }
     }
+
+    #[cfg(feature = "serde")]
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "==" => Some(Operator::Equal),
+            "!=" => Some(Operator::NotEqual),
+            "contains" => Some(Operator::Contain),
+            "=~" => Some(Operator::Match),
+            "!~" => Some(Operator::NotMatch),
+            _ => None,
+        }
+    }
 }
 
 fn escape_filter_value(value: &str) -> Cow<'_, str> {
@@ -284,4 +439,30 @@ mod tests {
             r#""((Artist == \"hello\") AND (Album == \"world\") AND (Title == \"foo\"))""#
         );
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn filter_serde() {
+        let filter = Filter::tag(Tag::Artist, r#"foo "bar" \ baz"#)
+            .and(!Filter::new(Tag::Album, Operator::Contain, "(AND)"))
+            .and(Filter::tag_absent(Tag::Other("MyTag".into())));
+
+        let json = serde_json::to_string(&filter).unwrap();
+        assert_eq!(
+            json,
+            r#""((Artist == \"foo \\\"bar\\\" \\\\ baz\") AND (!(Album contains \"(AND)\")) AND (MyTag == \"\"))""#
+        );
+        assert_eq!(serde_json::from_str::<Filter>(&json).unwrap(), filter);
+
+        for invalid in [
+            "",
+            "(Artist == \"foo\"",
+            "(Artist = \"foo\")",
+            "((Artist == \"foo\"))",
+            "(Artist == \"foo\") trailing",
+        ] {
+            let json = serde_json::to_string(invalid).unwrap();
+            assert!(serde_json::from_str::<Filter>(&json).is_err(), "{invalid}");
+        }
+    }
 }
@@ -15,14 +15,27 @@
 //!
 //! # Crate Features
 //!
-//! | Feature  | Description                       |
-//! |----------|-----------------------------------|
-//! | `chrono` | Support for parsing [`Timestamp`] |
+//! | Feature  | Description                                                               |
+//! |----------|---------------------------------------------------------------------------|
+//! | `chrono` | Support for parsing [`Timestamp`] using `chrono`                          |
+//! | `time`   | Support for parsing [`Timestamp`] using `time`                            |
+//! | `jiff`   | Support for parsing [`Timestamp`] using `jiff`                            |
+//! | `derive` | Derive macros for defining custom commands and responses                  |
+//! | `serde`  | `Serialize`/`Deserialize` implementations for responses and related types |
+//! | `cache`  | Caching album art in memory and on disk                                   |
+//! | `image`  | Generating and caching thumbnails of album art                            |
+//!
+//! With the `serde` feature, durations are represented as (fractional) seconds, and timestamps
+//! and tags as the strings used by MPD.
 //!
 //! [`Timestamp`]: responses::Timestamp
 
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
+// Allows the derive macros to be used in tests of this crate
+#[cfg(all(test, feature = "derive"))]
+extern crate self as mpd_client;
+
 pub mod client;
 pub mod commands;
 pub mod filter;
@@ -0,0 +1,345 @@
+use std::{error::Error, fmt, str::FromStr};
+
+use crate::responses::{FromFieldValue, TypedResponseError};
+
+/// Base rate of DSD formats, `dsd64` has a bit rate of 64 times this.
+const DSD_BASE_RATE: u32 = 44100;
+
+/// An audio format, as used by MPD in the `samplerate:bits:channels` notation.
+///
+/// Fields which are `None` are unspecified, which MPD represents using `*`. This occurs mostly in
+/// output configurations, e.g. in the `allowed_formats` attribute of an [`Output`].
+///
+/// ```
+/// use mpd_client::responses::{AudioFormat, SampleFormat};
+///
+/// let format: AudioFormat = "44100:f:2".parse().unwrap();
+/// assert_eq!(format.sample_rate, Some(44100));
+/// assert_eq!(format.sample_format, Some(SampleFormat::Float));
+/// assert_eq!(format.channels, Some(2));
+///
+/// let dsd: AudioFormat = "dsd64:2".parse().unwrap();
+/// assert_eq!(dsd.dsd_rate(), Some(64));
+/// assert_eq!(dsd.to_string(), "dsd64:2");
+/// ```
+///
+/// [`Output`]: crate::responses::Output
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AudioFormat {
+    /// The sample rate in Hz.
+    ///
+    /// For DSD formats, this is the number of 8-bit samples per second, i.e. the bit rate divided
+    /// by 8. See [`AudioFormat::dsd_rate`] for the more common notation.
+    pub sample_rate: Option<u32>,
+    /// The format of the samples.
+    pub sample_format: Option<SampleFormat>,
+    /// The number of channels.
+    pub channels: Option<u8>,
+    /// Whether DSD samples are sent to the output as DSD over PCM (DoP).
+    ///
+    /// This is indicated using a `=dop` suffix, and only occurs in output configurations.
+    pub dop: bool,
+}
+
+impl AudioFormat {
+    /// If this is a DSD format, get its rate as a multiple of 44.1 kHz (e.g. 64 for DSD64).
+    pub fn dsd_rate(&self) -> Option<u32> {
+        if self.sample_format != Some(SampleFormat::Dsd) {
+            return None;
+        }
+
+        let bit_rate = self.sample_rate?.checked_mul(8)?;
+
+        if bit_rate % DSD_BASE_RATE == 0 {
+            Some(bit_rate / DSD_BASE_RATE)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if all components of the format are specified.
+    pub fn is_fully_specified(&self) -> bool {
+        self.sample_rate.is_some() && self.sample_format.is_some() && self.channels.is_some()
+    }
+}
+
+impl FromStr for AudioFormat {
+    type Err = AudioFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (s, dop) = match s.strip_suffix("=dop") {
+            Some(s) => (s, true),
+            None => (s, false),
+        };
+
+        let parts = s.split(':').collect::<Vec<_>>();
+
+        let (sample_rate, sample_format, channels) = match parts[..] {
+            [rate, channels] => {
+                // `dsd64:2` notation
+                let rate = rate
+                    .strip_prefix("dsd")
+                    .and_then(|r| r.parse::<u32>().ok())
+                    .and_then(|r| r.checked_mul(DSD_BASE_RATE))
+                    .filter(|r| r % 8 == 0 && *r > 0)
+                    .ok_or(AudioFormatError::SampleRate)?;
+
+                (Some(rate / 8), Some(SampleFormat::Dsd), channels)
+            }
+            [rate, format, channels] => {
+                let rate = match rate {
+                    "*" => None,
+                    rate => match rate.parse() {
+                        Ok(rate) if rate > 0 => Some(rate),
+                        _ => return Err(AudioFormatError::SampleRate),
+                    },
+                };
+
+                let format = match format {
+                    "*" => None,
+                    format => Some(SampleFormat::from_raw(format)?),
+                };
+
+                (rate, format, channels)
+            }
+            _ => return Err(AudioFormatError::Syntax),
+        };
+
+        let channels = match channels {
+            // The channels may also be omitted entirely in output configurations
+            "*" | "" => None,
+            channels => match channels.parse() {
+                Ok(channels) if channels > 0 => Some(channels),
+                _ => return Err(AudioFormatError::Channels),
+            },
+        };
+
+        Ok(Self {
+            sample_rate,
+            sample_format,
+            channels,
+            dop,
+        })
+    }
+}
+
+impl fmt::Display for AudioFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(rate) = self.dsd_rate() {
+            write!(f, "dsd{rate}")?;
+        } else {
+            match self.sample_rate {
+                Some(rate) => write!(f, "{rate}:")?,
+                None => write!(f, "*:")?,
+            }
+
+            match self.sample_format {
+                Some(format) => write!(f, "{format}")?,
+                None => write!(f, "*")?,
+            }
+        }
+
+        match self.channels {
+            Some(channels) => write!(f, ":{channels}")?,
+            None => write!(f, ":*")?,
+        }
+
+        if self.dop {
+            write!(f, "=dop")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromFieldValue for AudioFormat {
+    fn from_value(v: String, field: &str) -> Result<Self, TypedResponseError> {
+        match v.parse() {
+            Ok(format) => Ok(format),
+            Err(e) => Err(TypedResponseError::invalid_value(field, v).source(e)),
+        }
+    }
+}
+
+/// Format of samples in an [`AudioFormat`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum SampleFormat {
+    /// Signed 8-bit integer samples.
+    S8,
+    /// Signed 16-bit integer samples.
+    S16,
+    /// Signed 24-bit integer samples (in 32-bit containers).
+    S24,
+    /// Signed 32-bit integer samples.
+    S32,
+    /// 32-bit floating point samples (`f`).
+    Float,
+    /// Direct Stream Digital (DSD) samples.
+    Dsd,
+}
+
+impl SampleFormat {
+    fn from_raw(raw: &str) -> Result<Self, AudioFormatError> {
+        match raw {
+            "8" => Ok(Self::S8),
+            "16" => Ok(Self::S16),
+            "24" => Ok(Self::S24),
+            "32" => Ok(Self::S32),
+            "f" => Ok(Self::Float),
+            "dsd" => Ok(Self::Dsd),
+            _ => Err(AudioFormatError::SampleFormat),
+        }
+    }
+
+    /// The bit depth of integer samples, or `None` for floating point and DSD samples.
+    pub fn bits(&self) -> Option<u8> {
+        match self {
+            Self::S8 => Some(8),
+            Self::S16 => Some(16),
+            Self::S24 => Some(24),
+            Self::S32 => Some(32),
+            Self::Float | Self::Dsd => None,
+        }
+    }
+}
+
+impl fmt::Display for SampleFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.bits() {
+            Some(bits) => write!(f, "{bits}"),
+            None if *self == Self::Float => write!(f, "f"),
+            None => write!(f, "dsd"),
+        }
+    }
+}
+
+/// Error returned when parsing an invalid [`AudioFormat`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AudioFormatError {
+    /// The format did not consist of the expected number of components.
+    Syntax,
+    /// The sample rate was invalid.
+    SampleRate,
+    /// The sample format was invalid.
+    SampleFormat,
+    /// The number of channels was invalid.
+    Channels,
+}
+
+impl fmt::Display for AudioFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax => write!(f, "invalid audio format"),
+            Self::SampleRate => write!(f, "invalid sample rate"),
+            Self::SampleFormat => write!(f, "invalid sample format"),
+            Self::Channels => write!(f, "invalid number of channels"),
+        }
+    }
+}
+
+impl Error for AudioFormatError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(
+        sample_rate: Option<u32>,
+        sample_format: Option<SampleFormat>,
+        channels: Option<u8>,
+    ) -> AudioFormat {
+        AudioFormat {
+            sample_rate,
+            sample_format,
+            channels,
+            dop: false,
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "44100:16:2".parse(),
+            Ok(format(Some(44100), Some(SampleFormat::S16), Some(2)))
+        );
+        assert_eq!(
+            "192000:f:6".parse(),
+            Ok(format(Some(192000), Some(SampleFormat::Float), Some(6)))
+        );
+        assert_eq!(
+            "*:24:*".parse(),
+            Ok(format(None, Some(SampleFormat::S24), None))
+        );
+        assert_eq!(
+            "*:dsd:".parse(),
+            Ok(format(None, Some(SampleFormat::Dsd), None))
+        );
+        assert_eq!(
+            "dsd64:2".parse(),
+            Ok(format(Some(352800), Some(SampleFormat::Dsd), Some(2)))
+        );
+        assert_eq!(
+            "352800:dsd:2".parse(),
+            Ok(format(Some(352800), Some(SampleFormat::Dsd), Some(2)))
+        );
+        assert_eq!(
+            "dsd128:=dop".parse(),
+            Ok(AudioFormat {
+                dop: true,
+                ..format(Some(705600), Some(SampleFormat::Dsd), None)
+            })
+        );
+
+        assert_eq!(
+            "44100:16".parse::<AudioFormat>(),
+            Err(AudioFormatError::SampleRate)
+        );
+        assert_eq!("".parse::<AudioFormat>(), Err(AudioFormatError::Syntax));
+        assert_eq!(
+            "44100:12:2".parse::<AudioFormat>(),
+            Err(AudioFormatError::SampleFormat)
+        );
+        assert_eq!(
+            "0:16:2".parse::<AudioFormat>(),
+            Err(AudioFormatError::SampleRate)
+        );
+        assert_eq!(
+            "44100:16:0".parse::<AudioFormat>(),
+            Err(AudioFormatError::Channels)
+        );
+        assert_eq!(
+            "44100:16:2:2".parse::<AudioFormat>(),
+            Err(AudioFormatError::Syntax)
+        );
+    }
+
+    #[test]
+    fn display() {
+        for raw in ["44100:16:2", "*:f:*", "dsd256:2", "dsd64:*=dop", "*:*:*"] {
+            let format: AudioFormat = raw.parse().unwrap();
+            assert_eq!(format.to_string(), raw);
+        }
+
+        let format: AudioFormat = "352800:dsd:2".parse().unwrap();
+        assert_eq!(format.to_string(), "dsd64:2");
+    }
+
+    #[test]
+    fn dsd_rate() {
+        let format: AudioFormat = "dsd512:2".parse().unwrap();
+        assert_eq!(format.dsd_rate(), Some(512));
+        assert!(format.is_fully_specified());
+
+        let format: AudioFormat = "*:dsd:2".parse().unwrap();
+        assert_eq!(format.dsd_rate(), None);
+        assert!(!format.is_fully_specified());
+
+        let format: AudioFormat = "44100:32:2".parse().unwrap();
+        assert_eq!(format.dsd_rate(), None);
+        assert_eq!(format.sample_format.and_then(|f| f.bits()), Some(32));
+    }
+}
@@ -0,0 +1,126 @@
+use crate::responses::KeyValuePair;
+
+/// Raw comments (tags) of a song file, as returned by [`readcomments`].
+///
+/// Unlike [`Song::tags`], this includes all tags present in the file, not only those known to
+/// MPD, using the names from the file. The comments are kept in the order they were returned, and
+/// a name may occur multiple times.
+///
+/// ```
+/// # use mpd_client::responses::Comments;
+/// let comments = Comments::from_iter([
+///     (String::from("ARTIST"), String::from("Foo")),
+///     (String::from("ARTIST"), String::from("Bar")),
+///     (String::from("REPLAYGAIN_TRACK_GAIN"), String::from("-6.5 dB")),
+/// ]);
+///
+/// assert_eq!(comments.get("ARTIST"), Some("Foo"));
+/// assert_eq!(comments.get_all("ARTIST").collect::<Vec<_>>(), ["Foo", "Bar"]);
+/// assert_eq!(comments.len(), 3);
+/// ```
+///
+/// [`readcomments`]: crate::commands::definitions::ReadComments
+/// [`Song::tags`]: crate::responses::Song::tags
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Comments(Vec<(String, String)>);
+
+impl Comments {
+    /// Get the first value of the comment with the given name.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find_map(|(k, v)| if k == name { Some(v.as_str()) } else { None })
+    }
+
+    /// Get all values of the comment with the given name, in order.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.0
+            .iter()
+            .filter(move |(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns `true` if there is a comment with the given name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Iterate over the names and values of all comments, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// The number of comments, counting each value separately.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no comments.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Get the names and values of all comments, in order.
+    pub fn into_inner(self) -> Vec<(String, String)> {
+        self.0
+    }
+
+    pub(crate) fn from_frame(raw: impl IntoIterator<Item = KeyValuePair>) -> Self {
+        raw.into_iter()
+            .map(|(k, v)| (String::from(&*k), v))
+            .collect()
+    }
+}
+
+impl FromIterator<(String, String)> for Comments {
+    fn from_iter<T: IntoIterator<Item = (String, String)>>(iter: T) -> Self {
+        Comments(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Comments {
+    type Item = (String, String);
+    type IntoIter = std::vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn comments() {
+        let fields = [
+            ("TITLE", "Foo"),
+            ("ARTIST", "Bar"),
+            ("MUSICBRAINZ_TRACKID", "abc"),
+            ("ARTIST", "Baz"),
+        ]
+        .map(|(k, v)| (Arc::from(k), String::from(v)));
+
+        let comments = Comments::from_frame(fields);
+
+        assert_eq!(comments.len(), 4);
+        assert_eq!(comments.get("ARTIST"), Some("Bar"));
+        assert_eq!(
+            comments.get_all("ARTIST").collect::<Vec<_>>(),
+            ["Bar", "Baz"]
+        );
+        assert!(comments.contains("MUSICBRAINZ_TRACKID"));
+        assert_eq!(comments.get("artist"), None);
+        assert_eq!(
+            comments.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+            ["TITLE", "ARTIST", "MUSICBRAINZ_TRACKID", "ARTIST"]
+        );
+    }
+}
@@ -9,11 +9,13 @@ use crate::{
 
 /// Response to the [`Count`][crate::commands::Count] command.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub struct Count {
     /// Number of songs
     pub songs: u64,
     /// Total playtime of the songs
+    #[cfg_attr(feature = "serde", serde(with = "crate::responses::seconds"))]
     pub playtime: Duration,
 }
 
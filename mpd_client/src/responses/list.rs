@@ -61,6 +61,51 @@ impl<const N: usize> List<N> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for List<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(serde::Serialize)]
+        struct List<'a> {
+            primary_tag: &'a Tag,
+            groupings: &'a [Tag],
+            fields: &'a [(Tag, String)],
+        }
+
+        serde::Serialize::serialize(
+            &List {
+                primary_tag: &self.primary_tag,
+                groupings: &self.groupings,
+                fields: &self.fields,
+            },
+            serializer,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for List<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct List {
+            primary_tag: Tag,
+            groupings: Vec<Tag>,
+            fields: Vec<(Tag, String)>,
+        }
+
+        let list = <List as serde::Deserialize>::deserialize(deserializer)?;
+
+        let groupings = list.groupings.try_into().map_err(|g: Vec<Tag>| {
+            serde::de::Error::invalid_length(g.len(), &N.to_string().as_str())
+        })?;
+
+        Ok(Self {
+            primary_tag: list.primary_tag,
+            groupings,
+            fields: list.fields,
+        })
+    }
+}
+
 impl<'a> IntoIterator for &'a List<0> {
     type Item = &'a str;
 
@@ -226,4 +271,26 @@ mod tests {
         assert_eq!(iter.next(), Some(("Title 4", ["Qwert", "Asdf"])));
         assert_eq!(iter.next(), None);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn list_serde() {
+        let list = List {
+            primary_tag: Tag::Title,
+            groupings: [Tag::Album],
+            fields: vec![
+                (Tag::Album, String::from("Bar")),
+                (Tag::Title, String::from("Title 1")),
+            ],
+        };
+
+        let json = serde_json::to_string(&list).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"primary_tag":"Title","groupings":["Album"],"fields":[["Album","Bar"],["Title","Title 1"]]}"#
+        );
+        assert_eq!(serde_json::from_str::<List<1>>(&json).unwrap(), list);
+        assert!(serde_json::from_str::<List<2>>(&json).is_err());
+    }
 }
@@ -1,8 +1,14 @@
 //! Typed responses to individual commands.
 
+mod audio_format;
+mod comments;
 mod count;
 mod list;
+mod mount;
+mod output;
 mod playlist;
+#[cfg(feature = "serde")]
+mod seconds;
 mod song;
 mod sticker;
 mod timestamp;
@@ -12,15 +18,23 @@ use std::{error::Error, fmt, num::ParseIntError, str::FromStr, sync::Arc, time::
 use bytes::BytesMut;
 use mpd_protocol::response::Frame;
 
+pub(crate) use self::sticker::parse_sticker_names_types;
 pub use self::{
+    audio_format::{AudioFormat, AudioFormatError, SampleFormat},
+    comments::Comments,
     count::Count,
     list::{GroupedListValuesIter, List, ListValuesIntoIter, ListValuesIter},
+    mount::{Mount, Neighbor},
+    output::Output,
     playlist::Playlist,
     song::{Song, SongInQueue, SongRange},
     sticker::{StickerFind, StickerGet, StickerList},
     timestamp::Timestamp,
 };
-use crate::commands::{ReplayGainMode, SingleMode, SongId, SongPosition};
+#[cfg(feature = "derive")]
+pub use mpd_client_derive::FromFrame;
+
+use crate::commands::{ConsumeMode, ReplayGainMode, SingleMode, SongId, SongPosition};
 
 type KeyValuePair = (Arc<str>, String);
 
@@ -125,11 +139,38 @@ impl Error for TypedResponseError {
 }
 
 /// Types which can be converted from a field value.
-pub(crate) trait FromFieldValue: Sized {
+///
+/// This is used when parsing [responses][FromFrame], and can be implemented for custom types.
+pub trait FromFieldValue: Sized {
     /// Convert the value.
+    ///
+    /// # Errors
+    ///
+    /// This should return an error if the value is invalid. `field` is the name of the field the
+    /// value belongs to, for use in the error.
     fn from_value(v: String, field: &str) -> Result<Self, TypedResponseError>;
 }
 
+/// Types which can be parsed from a response frame.
+///
+/// This is mostly useful for defining [custom commands][crate::commands::Command]. With the
+/// `derive` feature enabled, this can be derived for structs using `#[derive(FromFrame)]`.
+pub trait FromFrame: Sized {
+    /// Parse the frame.
+    ///
+    /// # Errors
+    ///
+    /// This should return an error if the frame is missing required fields or contains invalid
+    /// values.
+    fn from_frame(frame: Frame) -> Result<Self, TypedResponseError>;
+}
+
+impl FromFieldValue for String {
+    fn from_value(v: String, _: &str) -> Result<Self, TypedResponseError> {
+        Ok(v)
+    }
+}
+
 impl FromFieldValue for bool {
     fn from_value(v: String, field: &str) -> Result<Self, TypedResponseError> {
         match &*v {
@@ -157,6 +198,17 @@ impl FromFieldValue for PlayState {
     }
 }
 
+impl FromFieldValue for ConsumeMode {
+    fn from_value(v: String, field: &str) -> Result<Self, TypedResponseError> {
+        match &*v {
+            "0" => Ok(ConsumeMode::Disabled),
+            "1" => Ok(ConsumeMode::Enabled),
+            "oneshot" => Ok(ConsumeMode::Oneshot),
+            _ => Err(TypedResponseError::invalid_value(field, v)),
+        }
+    }
+}
+
 impl FromFieldValue for ReplayGainMode {
     fn from_value(v: String, field: &str) -> Result<Self, TypedResponseError> {
         match &*v {
@@ -201,6 +253,22 @@ impl FromFieldValue for usize {
     }
 }
 
+impl FromFieldValue for f32 {
+    fn from_value(v: String, field: &str) -> Result<Self, TypedResponseError> {
+        v.parse()
+            .map_err(|e| TypedResponseError::invalid_value(field, v).source(e))
+    }
+}
+
+/// Parse the `volume` field, which is either missing or `-1` if the server has no mixer.
+pub(crate) fn parse_volume(frame: &mut Frame) -> Result<Option<u8>, TypedResponseError> {
+    match frame.get("volume") {
+        None => Ok(None),
+        Some(v) if v == "-1" => Ok(None),
+        Some(v) => u8::from_value(v, "volume").map(Some),
+    }
+}
+
 /// Get a *required* value for the given field, as the given type.
 pub(crate) fn value<V: FromFieldValue>(
     frame: &mut Frame,
@@ -262,6 +330,7 @@ fn parse_duration<V: AsRef<str> + Into<String>>(
 
 /// Possible playback states.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum PlayState {
     Stopped,
@@ -276,6 +345,7 @@ pub enum PlayState {
 /// [`replay_gain_status`]: crate::commands::definitions::ReplayGainStatus
 /// [replay-gain-status-command]: https://www.musicpd.org/doc/html/protocol.html#command-replay-gain-status
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 #[non_exhaustive]
 pub struct ReplayGainStatus {
@@ -297,24 +367,39 @@ impl ReplayGainStatus {
 ///
 /// [`status`]: crate::commands::definitions::Status
 /// [status-command]: https://www.musicpd.org/doc/html/protocol.html#command-status
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 #[non_exhaustive]
 pub struct Status {
-    pub volume: u8,
+    /// The volume, or `None` if the server has no mixer.
+    pub volume: Option<u8>,
     pub state: PlayState,
     pub repeat: bool,
     pub random: bool,
-    pub consume: bool,
+    pub consume: ConsumeMode,
     pub single: SingleMode,
     pub playlist_version: u32,
     pub playlist_length: usize,
     pub current_song: Option<(SongPosition, SongId)>,
     pub next_song: Option<(SongPosition, SongId)>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::responses::seconds::option"))]
     pub elapsed: Option<Duration>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::responses::seconds::option"))]
     pub duration: Option<Duration>,
     pub bitrate: Option<u64>,
+    /// Format of the audio currently being decoded.
+    pub audio: Option<AudioFormat>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::responses::seconds"))]
     pub crossfade: Duration,
+    /// Volume threshold in decibels at which songs overlap when using MixRamp.
+    pub mixramp_db: Option<f32>,
+    /// Additional time subtracted from the overlap calculated by MixRamp. `None` if MixRamp is
+    /// disabled.
+    #[cfg_attr(feature = "serde", serde(with = "crate::responses::seconds::option"))]
+    pub mixramp_delay: Option<Duration>,
+    /// The name of the last loaded stored playlist.
+    pub last_loaded_playlist: Option<String>,
     pub update_job: Option<u64>,
     pub error: Option<String>,
     pub partition: Option<String>,
@@ -349,7 +434,7 @@ impl Status {
         let f = &mut raw;
 
         Ok(Self {
-            volume: optional_value(f, "volume")?.unwrap_or(0),
+            volume: parse_volume(f)?,
             state: value(f, "state")?,
             repeat: value(f, "repeat")?,
             random: value(f, "random")?,
@@ -362,7 +447,11 @@ impl Status {
             elapsed: optional_value(f, "elapsed")?,
             duration,
             bitrate: optional_value(f, "bitrate")?,
+            audio: optional_value(f, "audio")?,
             crossfade: optional_value(f, "xfade")?.unwrap_or(Duration::ZERO),
+            mixramp_db: optional_value(f, "mixrampdb")?,
+            mixramp_delay: optional_value(f, "mixrampdelay")?,
+            last_loaded_playlist: f.get("lastloadedplaylist"),
             update_job: optional_value(f, "update_job")?,
             error: f.get("error"),
             partition: f.get("partition"),
@@ -374,14 +463,18 @@ impl Status {
 ///
 /// [`stats`]: crate::commands::definitions::Stats
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 #[non_exhaustive]
 pub struct Stats {
     pub artists: u64,
     pub albums: u64,
     pub songs: u64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::responses::seconds"))]
     pub uptime: Duration,
+    #[cfg_attr(feature = "serde", serde(with = "crate::responses::seconds"))]
     pub playtime: Duration,
+    #[cfg_attr(feature = "serde", serde(with = "crate::responses::seconds"))]
     pub db_playtime: Duration,
     /// Raw server UNIX timestamp of last database update.
     pub db_last_update: u64,
@@ -405,6 +498,7 @@ impl Stats {
 /// Response to the [`albumart`][crate::commands::AlbumArt] and
 /// [`readpicture`][crate::commands::AlbumArtEmbedded] commands.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub struct AlbumArt {
     /// The total size in bytes of the file.
@@ -429,6 +523,96 @@ impl AlbumArt {
     }
 }
 
+/// Response to the [`decoders`] command, describing a single decoder plugin.
+///
+/// [`decoders`]: crate::commands::definitions::Decoders
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Decoder {
+    /// Name of the plugin.
+    pub plugin: String,
+    /// File suffixes the plugin can decode.
+    pub suffixes: Vec<String>,
+    /// MIME types the plugin can decode.
+    pub mime_types: Vec<String>,
+}
+
+impl Decoder {
+    pub(crate) fn parse_frame(
+        raw: impl IntoIterator<Item = KeyValuePair>,
+    ) -> Result<Vec<Self>, TypedResponseError> {
+        let mut out: Vec<Decoder> = Vec::new();
+
+        for (key, value) in raw {
+            if &*key == "plugin" {
+                out.push(Decoder {
+                    plugin: value,
+                    suffixes: Vec::new(),
+                    mime_types: Vec::new(),
+                });
+                continue;
+            }
+
+            let Some(decoder) = out.last_mut() else {
+                return Err(TypedResponseError::unexpected_field("plugin", &*key));
+            };
+
+            match &*key {
+                "suffix" => decoder.suffixes.push(value),
+                "mime_type" => decoder.mime_types.push(value),
+                other => return Err(TypedResponseError::unexpected_field("suffix", other)),
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+/// Response to the [`config`] command.
+///
+/// [`config`]: crate::commands::definitions::Config
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Config {
+    /// Absolute path of the music directory on the server.
+    pub music_directory: String,
+    /// Absolute path of the playlist directory on the server, if configured.
+    pub playlist_directory: Option<String>,
+    /// Whether the server supports regular expressions in filters.
+    pub pcre: bool,
+}
+
+impl Config {
+    pub(crate) fn from_frame(mut raw: Frame) -> Result<Self, TypedResponseError> {
+        let f = &mut raw;
+        Ok(Self {
+            music_directory: value(f, "music_directory")?,
+            playlist_directory: f.get("playlist_directory"),
+            pcre: optional_value(f, "pcre")?.unwrap_or(false),
+        })
+    }
+}
+
+/// Parse a response consisting only of repetitions of the given field.
+pub(crate) fn parse_single_field_list(
+    frame: Frame,
+    field: &'static str,
+) -> Result<Vec<String>, TypedResponseError> {
+    let mut response = Vec::with_capacity(frame.fields_len());
+
+    for (key, value) in frame {
+        if &*key != field {
+            return Err(TypedResponseError::unexpected_field(field, &*key));
+        }
+
+        response.push(value);
+    }
+
+    Ok(response)
+}
+
 /// Parse response for the [`crate::commands::ReadChannelMessages`] command.
 pub(crate) fn parse_channel_messages<F>(
     fields: F,
@@ -497,4 +681,35 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn decoder_parsing() {
+        let fields = vec![
+            (Arc::from("plugin"), String::from("mad")),
+            (Arc::from("suffix"), String::from("mp3")),
+            (Arc::from("suffix"), String::from("mp2")),
+            (Arc::from("mime_type"), String::from("audio/mpeg")),
+            (Arc::from("plugin"), String::from("mpcdec")),
+            (Arc::from("suffix"), String::from("mpc")),
+        ];
+
+        assert_eq!(
+            Decoder::parse_frame(fields).unwrap(),
+            vec![
+                Decoder {
+                    plugin: String::from("mad"),
+                    suffixes: vec![String::from("mp3"), String::from("mp2")],
+                    mime_types: vec![String::from("audio/mpeg")],
+                },
+                Decoder {
+                    plugin: String::from("mpcdec"),
+                    suffixes: vec![String::from("mpc")],
+                    mime_types: Vec::new(),
+                },
+            ]
+        );
+
+        let fields = vec![(Arc::from("suffix"), String::from("mp3"))];
+        assert_matches!(Decoder::parse_frame(fields), Err(_));
+    }
 }
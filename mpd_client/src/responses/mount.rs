@@ -0,0 +1,133 @@
+use crate::responses::{KeyValuePair, TypedResponseError};
+
+/// A storage mount, as returned by [`listmounts`].
+///
+/// [`listmounts`]: crate::commands::definitions::ListMounts
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Mount {
+    /// Path the storage is mounted at, relative to the music directory. The root mount has an
+    /// empty path.
+    pub path: String,
+    /// URI of the mounted storage, if known.
+    pub storage: Option<String>,
+}
+
+impl Mount {
+    pub(crate) fn parse_frame(
+        raw: impl IntoIterator<Item = KeyValuePair>,
+    ) -> Result<Vec<Self>, TypedResponseError> {
+        let mut out: Vec<Mount> = Vec::new();
+
+        for (key, value) in raw {
+            match &*key {
+                "mount" => out.push(Mount {
+                    path: value,
+                    storage: None,
+                }),
+                "storage" => match out.last_mut() {
+                    Some(mount) if mount.storage.is_none() => mount.storage = Some(value),
+                    _ => return Err(TypedResponseError::unexpected_field("mount", "storage")),
+                },
+                other => return Err(TypedResponseError::unexpected_field("mount", other)),
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+/// A neighbor storage discovered on the network, as returned by [`listneighbors`].
+///
+/// [`listneighbors`]: crate::commands::definitions::ListNeighbors
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Neighbor {
+    /// URI of the storage, which can be passed to the [`mount`] command.
+    ///
+    /// [`mount`]: crate::commands::definitions::Mount
+    pub uri: String,
+    /// Human-readable name of the storage.
+    pub name: Option<String>,
+}
+
+impl Neighbor {
+    pub(crate) fn parse_frame(
+        raw: impl IntoIterator<Item = KeyValuePair>,
+    ) -> Result<Vec<Self>, TypedResponseError> {
+        let mut out: Vec<Neighbor> = Vec::new();
+
+        for (key, value) in raw {
+            match &*key {
+                "neighbor" => out.push(Neighbor {
+                    uri: value,
+                    name: None,
+                }),
+                "name" => match out.last_mut() {
+                    Some(neighbor) if neighbor.name.is_none() => neighbor.name = Some(value),
+                    _ => return Err(TypedResponseError::unexpected_field("neighbor", "name")),
+                },
+                other => return Err(TypedResponseError::unexpected_field("neighbor", other)),
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[test]
+    fn mounts() {
+        let fields = vec![
+            (Arc::from("mount"), String::from("")),
+            (Arc::from("storage"), String::from("/home/foo/music")),
+            (Arc::from("mount"), String::from("nas")),
+            (
+                Arc::from("storage"),
+                String::from("nfs://192.168.1.4/export/mp3"),
+            ),
+        ];
+
+        assert_eq!(
+            Mount::parse_frame(fields).unwrap(),
+            vec![
+                Mount {
+                    path: String::new(),
+                    storage: Some(String::from("/home/foo/music")),
+                },
+                Mount {
+                    path: String::from("nas"),
+                    storage: Some(String::from("nfs://192.168.1.4/export/mp3")),
+                },
+            ]
+        );
+
+        let fields = vec![(Arc::from("storage"), String::from("/home/foo/music"))];
+        assert_matches!(Mount::parse_frame(fields), Err(_));
+    }
+
+    #[test]
+    fn neighbors() {
+        let fields = vec![
+            (Arc::from("neighbor"), String::from("smb://FOO")),
+            (Arc::from("name"), String::from("FOO (Samba 4.1.11-Debian)")),
+        ];
+
+        assert_eq!(
+            Neighbor::parse_frame(fields).unwrap(),
+            vec![Neighbor {
+                uri: String::from("smb://FOO"),
+                name: Some(String::from("FOO (Samba 4.1.11-Debian)")),
+            }]
+        );
+    }
+}
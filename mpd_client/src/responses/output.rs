@@ -0,0 +1,152 @@
+use crate::responses::{
+    AudioFormat, AudioFormatError, FromFieldValue, KeyValuePair, TypedResponseError,
+};
+
+/// An audio output, as returned by [`outputs`].
+///
+/// [`outputs`]: crate::commands::definitions::Outputs
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Output {
+    /// ID of the output.
+    pub id: u32,
+    /// Name of the output, as configured.
+    pub name: String,
+    /// Name of the output plugin, if reported by the server.
+    pub plugin: Option<String>,
+    /// Whether the output is enabled.
+    pub enabled: bool,
+    /// Runtime attributes of the output, in the order they were returned.
+    pub attributes: Vec<(String, String)>,
+}
+
+impl Output {
+    /// Get the value of the attribute with the given name.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find_map(|(k, v)| if k == name { Some(&**v) } else { None })
+    }
+
+    /// Get the formats the output is restricted to, from the `allowed_formats` attribute.
+    ///
+    /// This is empty if the attribute is not present or empty, meaning that all formats are
+    /// allowed.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the attribute contains an invalid format.
+    pub fn allowed_formats(&self) -> Result<Vec<AudioFormat>, AudioFormatError> {
+        match self.attribute("allowed_formats") {
+            Some(formats) => formats.split_whitespace().map(str::parse).collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Whether the output is configured to send DSD as DSD over PCM (DoP), from the `dop`
+    /// attribute.
+    pub fn dop(&self) -> Option<bool> {
+        match self.attribute("dop")? {
+            "0" => Some(false),
+            "1" => Some(true),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn parse_frame(
+        raw: impl IntoIterator<Item = KeyValuePair>,
+    ) -> Result<Vec<Self>, TypedResponseError> {
+        let mut out: Vec<Output> = Vec::new();
+
+        for (key, value) in raw {
+            if &*key == "outputid" {
+                out.push(Output {
+                    id: u32::from_value(value, "outputid")?,
+                    name: String::new(),
+                    plugin: None,
+                    enabled: false,
+                    attributes: Vec::new(),
+                });
+                continue;
+            }
+
+            let Some(output) = out.last_mut() else {
+                return Err(TypedResponseError::unexpected_field("outputid", &*key));
+            };
+
+            match &*key {
+                "outputname" => output.name = value,
+                "plugin" => output.plugin = Some(value),
+                "outputenabled" => output.enabled = bool::from_value(value, "outputenabled")?,
+                "attribute" => match value.split_once('=') {
+                    Some((k, v)) => output.attributes.push((k.to_owned(), v.to_owned())),
+                    None => return Err(TypedResponseError::invalid_value("attribute", value)),
+                },
+                other => return Err(TypedResponseError::unexpected_field("outputname", other)),
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use assert_matches::assert_matches;
+
+    use super::*;
+    use crate::responses::SampleFormat;
+
+    #[test]
+    fn outputs() {
+        let fields = [
+            ("outputid", "0"),
+            ("outputname", "DAC"),
+            ("plugin", "alsa"),
+            ("outputenabled", "1"),
+            (
+                "attribute",
+                "allowed_formats=96000:16:* 192000:24:* dsd64:=dop",
+            ),
+            ("attribute", "dop=1"),
+            ("outputid", "1"),
+            ("outputname", "Stream"),
+            ("outputenabled", "0"),
+        ]
+        .map(|(k, v)| (Arc::from(k), String::from(v)));
+
+        let outputs = Output::parse_frame(fields).unwrap();
+
+        assert_eq!(
+            outputs[1],
+            Output {
+                id: 1,
+                name: String::from("Stream"),
+                plugin: None,
+                enabled: false,
+                attributes: Vec::new(),
+            }
+        );
+
+        let dac = &outputs[0];
+        assert_eq!(dac.id, 0);
+        assert_eq!(dac.plugin.as_deref(), Some("alsa"));
+        assert!(dac.enabled);
+        assert_eq!(dac.dop(), Some(true));
+
+        let formats = dac.allowed_formats().unwrap();
+        assert_eq!(formats.len(), 3);
+        assert_eq!(formats[1].sample_format, Some(SampleFormat::S24));
+        assert_eq!(formats[2].dsd_rate(), Some(64));
+        assert!(formats[2].dop);
+
+        assert_eq!(outputs[1].allowed_formats(), Ok(Vec::new()));
+        assert_eq!(outputs[1].dop(), None);
+
+        let fields = [(Arc::from("outputname"), String::from("DAC"))];
+        assert_matches!(Output::parse_frame(fields), Err(_));
+    }
+}
@@ -6,6 +6,7 @@ use crate::responses::{FromFieldValue, Timestamp, TypedResponseError};
 ///
 /// [`listplaylists`]: crate::commands::definitions::GetPlaylists
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub struct Playlist {
     /// Name of the playlist.
@@ -4,7 +4,7 @@ use mpd_protocol::response::Frame;
 
 use crate::{
     commands::{SongId, SongPosition},
-    responses::{FromFieldValue, Timestamp, TypedResponseError, parse_duration},
+    responses::{AudioFormat, FromFieldValue, Timestamp, TypedResponseError, parse_duration},
     tag::Tag,
 };
 
@@ -12,6 +12,7 @@ use crate::{
 ///
 /// [`playlistinfo`]: crate::commands::definitions::Queue
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub struct SongInQueue {
     /// Position in queue.
@@ -64,6 +65,7 @@ impl SongInQueue {
 /// [playlist]: crate::commands::definitions::Queue
 /// [current song]: crate::commands::definitions::CurrentSong
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub struct Song {
     /// Unique identifier of the song. May be a file path relative to the library root, or an URL
@@ -72,13 +74,24 @@ pub struct Song {
     /// This is the `file` key as returned by MPD.
     pub url: String,
     /// The `duration` as returned by MPD.
+    #[cfg_attr(feature = "serde", serde(with = "crate::responses::seconds::option"))]
     pub duration: Option<Duration>,
     /// Tags in this response.
+    ///
+    /// This only contains [known tags][Tag], never [`Tag::Other`]. Fields with unrecognized names
+    /// are stored in [`other_fields`][Song::other_fields] instead.
     pub tags: HashMap<Tag, Vec<String>>,
-    /// The `format` as returned by MPD.
+    /// The `format` as returned by MPD. See [`Song::audio_format`] for a parsed version.
     pub format: Option<String>,
     /// Last modification date of the underlying file.
     pub last_modified: Option<Timestamp>,
+    /// Time the song was added to the database (protocol versions 0.24 and later).
+    pub added: Option<Timestamp>,
+    /// Fields which are neither known song attributes nor [known tags][Tag].
+    ///
+    /// This may contain attributes as well as tags added in newer protocol versions, since the
+    /// protocol does not allow telling them apart.
+    pub other_fields: HashMap<String, Vec<String>>,
 }
 
 impl Song {
@@ -88,6 +101,13 @@ impl Song {
         Path::new(&self.url)
     }
 
+    /// Get the parsed audio format of the song.
+    ///
+    /// Returns `None` if the format is unknown or invalid.
+    pub fn audio_format(&self) -> Option<AudioFormat> {
+        self.format.as_deref()?.parse().ok()
+    }
+
     /// Get all artists of the song.
     pub fn artists(&self) -> &[String] {
         self.tag_values(&Tag::Artist)
@@ -156,10 +176,13 @@ impl Song {
 
 /// Range used when playing only part of a [`Song`].
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SongRange {
     /// Start playback at this timestamp.
+    #[cfg_attr(feature = "serde", serde(with = "crate::responses::seconds"))]
     pub from: Duration,
     /// End at this timestamp (if the end is known).
+    #[cfg_attr(feature = "serde", serde(with = "crate::responses::seconds::option"))]
     pub to: Option<Duration>,
 }
 
@@ -193,6 +216,8 @@ struct SongBuilder {
     tags: HashMap<Tag, Vec<String>>,
     format: Option<String>,
     last_modified: Option<Timestamp>,
+    added: Option<Timestamp>,
+    other_fields: HashMap<String, Vec<String>>,
 }
 
 impl SongBuilder {
@@ -263,16 +288,22 @@ impl SongBuilder {
                 let lm = Timestamp::from_value(value, "Last-Modified")?;
                 self.last_modified = Some(lm);
             }
+            "Added" => self.added = Some(Timestamp::from_value(value, "Added")?),
             "Prio" => self.priority = u8::from_value(value, "Prio")?,
             "Pos" => self.position = usize::from_value(value, "Pos")?,
             "Id" => self.id = u64::from_value(value, "Id")?,
-            tag => {
-                // Anything else is a tag.
-                // It's fine to unwrap here because the protocol implementation already validated
-                // the field name
-                let tag = Tag::try_from(tag).unwrap();
-                self.tags.entry(tag).or_default().push(value);
-            }
+            other => match Tag::try_from(other) {
+                // Anything else is either a tag known to us ...
+                Ok(tag) if !matches!(tag, Tag::Other(_)) => {
+                    self.tags.entry(tag).or_default().push(value);
+                }
+                // ... or an unknown field
+                _ => self
+                    .other_fields
+                    .entry(other.to_owned())
+                    .or_default()
+                    .push(value),
+            },
         }
 
         Ok(None)
@@ -301,6 +332,8 @@ impl SongBuilder {
                 tags: self.tags,
                 format: self.format,
                 last_modified: self.last_modified,
+                added: self.added,
+                other_fields: self.other_fields,
             },
         }
     }
@@ -350,6 +383,8 @@ mod tests {
                     duration: Some(Duration::from_secs_f64(123.456)),
                     format: None,
                     last_modified: Some(Timestamp::from_value(TEST_TIMESTAMP.into(), "").unwrap()),
+                    added: None,
+                    other_fields: HashMap::new(),
                     tags: [(Tag::Title, vec![String::from("Foo")])].into(),
                 }
             }
@@ -369,6 +404,8 @@ mod tests {
                     duration: None,
                     format: None,
                     last_modified: None,
+                    added: None,
+                    other_fields: HashMap::new(),
                     tags: HashMap::new(),
                 }
             }
@@ -404,6 +441,8 @@ mod tests {
                     duration: None,
                     format: None,
                     last_modified: None,
+                    added: None,
+                    other_fields: HashMap::new(),
                     tags: HashMap::new(),
                 }
             }
@@ -412,6 +451,39 @@ mod tests {
         assert_matches!(builder.finish(), None);
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn song_serde() {
+        let song = SongInQueue {
+            position: SongPosition(5),
+            id: SongId(12),
+            priority: 0,
+            range: Some(SongRange {
+                from: Duration::from_secs_f64(1.5),
+                to: None,
+            }),
+            song: Song {
+                url: String::from("test.flac"),
+                duration: Some(Duration::from_secs_f64(123.456)),
+                format: None,
+                last_modified: Some(Timestamp::from_value(TEST_TIMESTAMP.into(), "").unwrap()),
+                added: None,
+                other_fields: HashMap::new(),
+                tags: [(Tag::Title, vec![String::from("Foo")])].into(),
+            },
+        };
+
+        let json = serde_json::to_value(&song).unwrap();
+
+        assert_eq!(json["position"], 5);
+        assert_eq!(json["range"]["from"], 1.5);
+        assert_eq!(json["song"]["duration"], 123.456);
+        assert_eq!(json["song"]["last_modified"], TEST_TIMESTAMP);
+        assert_eq!(json["song"]["tags"]["Title"][0], "Foo");
+
+        assert_eq!(serde_json::from_value::<SongInQueue>(json).unwrap(), song);
+    }
+
     #[test]
     fn song_builder_deprecated_time_field() {
         let mut builder = SongBuilder::default();
@@ -435,12 +507,56 @@ mod tests {
                 url: String::from("foo.flac"),
                 format: None,
                 last_modified: None,
+                added: None,
+                other_fields: HashMap::new(),
                 duration: Some(Duration::from_secs_f64(456.7)),
                 tags: HashMap::new(),
             }
         );
     }
 
+    #[test]
+    fn song_builder_added_and_other_fields() {
+        let mut builder = SongBuilder::default();
+
+        assert_matches!(builder.field("file", String::from("foo.flac")), Ok(None));
+        assert_matches!(
+            builder.field("Added", String::from(TEST_TIMESTAMP)),
+            Ok(None)
+        );
+        assert_matches!(builder.field("Mood", String::from("Happy")), Ok(None));
+        assert_matches!(builder.field("Foo", String::from("bar")), Ok(None));
+        assert_matches!(builder.field("Foo", String::from("baz")), Ok(None));
+
+        let song = builder.finish().unwrap().song;
+
+        assert_eq!(song.added.as_ref().unwrap().raw(), TEST_TIMESTAMP);
+        assert_eq!(song.tag_values(&Tag::Mood), [String::from("Happy")]);
+        assert_eq!(
+            song.other_fields["Foo"],
+            [String::from("bar"), String::from("baz")]
+        );
+        assert!(!song.tags.contains_key(&Tag::Other("Foo".into())));
+    }
+
+    #[test]
+    fn song_audio_format() {
+        let mut builder = SongBuilder::default();
+
+        assert_matches!(builder.field("file", String::from("foo.flac")), Ok(None));
+        assert_matches!(
+            builder.field("Format", String::from("96000:24:2")),
+            Ok(None)
+        );
+
+        let song = builder.finish().unwrap().song;
+        let format = song.audio_format().unwrap();
+
+        assert_eq!(format.sample_rate, Some(96000));
+        assert_eq!(format.sample_format.and_then(|f| f.bits()), Some(24));
+        assert_eq!(format.channels, Some(2));
+    }
+
     #[test]
     fn parse_range() {
         assert_eq!(
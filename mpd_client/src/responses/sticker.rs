@@ -8,6 +8,7 @@ use crate::responses::{KeyValuePair, TypedResponseError};
 ///
 /// [`sticker get`]: crate::commands::definitions::StickerGet
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub struct StickerGet {
     /// The sticker value
@@ -45,6 +46,7 @@ impl From<StickerGet> for String {
 ///
 /// [`sticker list`]: crate::commands::definitions::StickerList
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub struct StickerList {
     /// A map of sticker names to their values
@@ -74,10 +76,17 @@ impl From<StickerList> for HashMap<String, String> {
 ///
 /// [`sticker find`]: crate::commands::definitions::StickerFind
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub struct StickerFind {
     /// A map of songs to their sticker values
     pub value: HashMap<String, String>,
+    /// Songs and their sticker values, in the order they were returned by the server
+    ///
+    /// Use this when [sorting] the result.
+    ///
+    /// [sorting]: crate::commands::definitions::StickerFind::sort
+    pub entries: Vec<(String, String)>,
 }
 
 impl StickerFind {
@@ -85,6 +94,7 @@ impl StickerFind {
         raw: impl IntoIterator<Item = KeyValuePair>,
     ) -> Result<Self, TypedResponseError> {
         let mut value = HashMap::new();
+        let mut entries = Vec::new();
 
         let mut file = String::new();
 
@@ -93,14 +103,41 @@ impl StickerFind {
                 "file" => file = tag,
                 "sticker" => {
                     let (_, sticker_value) = parse_sticker_value(tag)?;
-                    value.insert(file.clone(), sticker_value);
+                    value.insert(file.clone(), sticker_value.clone());
+                    entries.push((file.clone(), sticker_value));
                 }
                 other => return Err(TypedResponseError::unexpected_field("sticker", other)),
             }
         }
 
-        Ok(Self { value })
+        Ok(Self { value, entries })
+    }
+}
+
+/// Parse response for the [`crate::commands::StickerNamesTypes`] command.
+///
+/// Returns `(name, type)` pairs.
+pub(crate) fn parse_sticker_names_types<F>(
+    fields: F,
+) -> Result<Vec<(String, String)>, TypedResponseError>
+where
+    F: IntoIterator<Item = KeyValuePair>,
+{
+    let mut response = Vec::new();
+    let mut name: Option<String> = None;
+
+    for (key, value) in fields {
+        match &*key {
+            "name" => name = Some(value),
+            "stickertype" => match &name {
+                Some(name) => response.push((name.clone(), value)),
+                None => return Err(TypedResponseError::unexpected_field("name", "stickertype")),
+            },
+            other => return Err(TypedResponseError::unexpected_field("name", other)),
+        }
     }
+
+    Ok(response)
 }
 
 /// Parses a `key=value` tag into its key and value strings
@@ -114,3 +151,56 @@ fn parse_sticker_value(mut tag: String) -> Result<(String, String), TypedRespons
         None => Err(TypedResponseError::invalid_value("sticker", tag)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[test]
+    fn sticker_find_order() {
+        let fields = vec![
+            (Arc::from("file"), String::from("b.flac")),
+            (Arc::from("sticker"), String::from("rating=5")),
+            (Arc::from("file"), String::from("a.flac")),
+            (Arc::from("sticker"), String::from("rating=3")),
+        ];
+
+        let response = StickerFind::from_frame(fields).unwrap();
+
+        assert_eq!(
+            response.entries,
+            vec![
+                (String::from("b.flac"), String::from("5")),
+                (String::from("a.flac"), String::from("3")),
+            ]
+        );
+        assert_eq!(response.value.len(), 2);
+    }
+
+    #[test]
+    fn sticker_names_types() {
+        let fields = vec![
+            (Arc::from("name"), String::from("rating")),
+            (Arc::from("stickertype"), String::from("song")),
+            (Arc::from("name"), String::from("playcount")),
+            (Arc::from("stickertype"), String::from("song")),
+            (Arc::from("stickertype"), String::from("playlist")),
+        ];
+
+        assert_eq!(
+            parse_sticker_names_types(fields).unwrap(),
+            vec![
+                (String::from("rating"), String::from("song")),
+                (String::from("playcount"), String::from("song")),
+                (String::from("playcount"), String::from("playlist")),
+            ]
+        );
+
+        let fields = vec![(Arc::from("stickertype"), String::from("song"))];
+        assert_matches!(parse_sticker_names_types(fields), Err(_));
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::{Duration, SystemTime};
+
 #[cfg(feature = "chrono")]
 use chrono::{DateTime, FixedOffset};
 
@@ -5,12 +7,21 @@ use crate::responses::{FromFieldValue, TypedResponseError};
 
 /// A timestamp, used for modification times.
 ///
-/// This is a newtype wrapper to allow the optional use of the `chrono` library.
+/// The timestamp is always available as a [`SystemTime`]. This is a newtype wrapper to allow the
+/// optional use of the `chrono`, `time` and `jiff` libraries.
+///
+/// Values which are not valid RFC 3339 timestamps are rejected when parsing responses, regardless
+/// of the enabled features.
 #[derive(Clone, Debug, Eq)]
 pub struct Timestamp {
     raw: String,
+    system_time: SystemTime,
     #[cfg(feature = "chrono")]
     chrono: DateTime<FixedOffset>,
+    #[cfg(feature = "time")]
+    time: time::OffsetDateTime,
+    #[cfg(feature = "jiff")]
+    jiff: jiff::Timestamp,
 }
 
 impl Timestamp {
@@ -19,11 +30,28 @@ impl Timestamp {
         &self.raw
     }
 
-    /// Returns the timestamp string as it was returned by the server.
+    /// Returns the timestamp as a [`SystemTime`].
+    pub fn system_time(&self) -> SystemTime {
+        self.system_time
+    }
+
+    /// Returns the timestamp as a `chrono` date and time.
     #[cfg(feature = "chrono")]
     pub fn chrono_datetime(&self) -> DateTime<FixedOffset> {
         self.chrono
     }
+
+    /// Returns the timestamp as a `time` date and time, with the offset returned by the server.
+    #[cfg(feature = "time")]
+    pub fn time_datetime(&self) -> time::OffsetDateTime {
+        self.time
+    }
+
+    /// Returns the timestamp as a `jiff` timestamp.
+    #[cfg(feature = "jiff")]
+    pub fn jiff_timestamp(&self) -> jiff::Timestamp {
+        self.jiff
+    }
 }
 
 impl PartialEq for Timestamp {
@@ -32,6 +60,12 @@ impl PartialEq for Timestamp {
     }
 }
 
+impl PartialEq<SystemTime> for Timestamp {
+    fn eq(&self, other: &SystemTime) -> bool {
+        &self.system_time == other
+    }
+}
+
 #[cfg(feature = "chrono")]
 impl PartialEq<DateTime<FixedOffset>> for Timestamp {
     fn eq(&self, other: &DateTime<FixedOffset>) -> bool {
@@ -39,6 +73,20 @@ impl PartialEq<DateTime<FixedOffset>> for Timestamp {
     }
 }
 
+#[cfg(feature = "time")]
+impl PartialEq<time::OffsetDateTime> for Timestamp {
+    fn eq(&self, other: &time::OffsetDateTime) -> bool {
+        &self.time == other
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl PartialEq<jiff::Timestamp> for Timestamp {
+    fn eq(&self, other: &jiff::Timestamp) -> bool {
+        &self.jiff == other
+    }
+}
+
 impl PartialOrd for Timestamp {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -51,6 +99,12 @@ impl Ord for Timestamp {
     }
 }
 
+impl PartialOrd<SystemTime> for Timestamp {
+    fn partial_cmp(&self, other: &SystemTime) -> Option<std::cmp::Ordering> {
+        self.system_time.partial_cmp(other)
+    }
+}
+
 #[cfg(feature = "chrono")]
 impl PartialOrd<DateTime<FixedOffset>> for Timestamp {
     fn partial_cmp(&self, other: &DateTime<FixedOffset>) -> Option<std::cmp::Ordering> {
@@ -58,19 +112,262 @@ impl PartialOrd<DateTime<FixedOffset>> for Timestamp {
     }
 }
 
+#[cfg(feature = "time")]
+impl PartialOrd<time::OffsetDateTime> for Timestamp {
+    fn partial_cmp(&self, other: &time::OffsetDateTime) -> Option<std::cmp::Ordering> {
+        self.time.partial_cmp(other)
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl PartialOrd<jiff::Timestamp> for Timestamp {
+    fn partial_cmp(&self, other: &jiff::Timestamp) -> Option<std::cmp::Ordering> {
+        self.jiff.partial_cmp(other)
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    fn from(timestamp: Timestamp) -> Self {
+        timestamp.system_time
+    }
+}
+
+/// Timestamps are serialized as the string returned by the server.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Timestamp {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.raw)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Timestamp {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Timestamp::from_value(raw, "timestamp").map_err(serde::de::Error::custom)
+    }
+}
+
 impl FromFieldValue for Timestamp {
-    #[cfg_attr(not(feature = "chrono"), allow(unused_variables))]
     fn from_value(v: String, field: &str) -> Result<Self, TypedResponseError> {
+        let Some(system_time) = parse_rfc3339(&v) else {
+            return Err(TypedResponseError::invalid_value(field, v));
+        };
+
         #[cfg(feature = "chrono")]
         let chrono = match DateTime::parse_from_rfc3339(&v) {
             Ok(v) => v,
             Err(e) => return Err(TypedResponseError::invalid_value(field, v).source(e)),
         };
 
+        #[cfg(feature = "time")]
+        let time =
+            match time::OffsetDateTime::parse(&v, &time::format_description::well_known::Rfc3339) {
+                Ok(v) => v,
+                Err(e) => return Err(TypedResponseError::invalid_value(field, v).source(e)),
+            };
+
+        #[cfg(feature = "jiff")]
+        let jiff = match v.parse::<jiff::Timestamp>() {
+            Ok(v) => v,
+            Err(e) => return Err(TypedResponseError::invalid_value(field, v).source(e)),
+        };
+
         Ok(Self {
             raw: v,
+            system_time,
             #[cfg(feature = "chrono")]
             chrono,
+            #[cfg(feature = "time")]
+            time,
+            #[cfg(feature = "jiff")]
+            jiff,
         })
     }
 }
+
+/// Parse an RFC 3339 timestamp (e.g. `2020-06-12T17:53:00Z`).
+fn parse_rfc3339(raw: &str) -> Option<SystemTime> {
+    let bytes = raw.as_bytes();
+
+    if bytes.len() < 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || !matches!(bytes[10], b'T' | b't' | b' ')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return None;
+    }
+
+    let year = digits(&bytes[0..4])?;
+    let month = digits(&bytes[5..7])?;
+    let day = digits(&bytes[8..10])?;
+    let hour = digits(&bytes[11..13])?;
+    let minute = digits(&bytes[14..16])?;
+    // Allow leap seconds
+    let second = digits(&bytes[17..19])?;
+
+    if !(1..=12).contains(&month)
+        || day == 0
+        || day > days_in_month(year, month)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return None;
+    }
+
+    // Optional fractional seconds
+    let mut rest = &raw[19..];
+    let mut nanos = 0;
+
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = fraction
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(fraction.len());
+
+        if len == 0 {
+            return None;
+        }
+
+        // Only nanosecond precision is supported, further digits are truncated
+        for (i, digit) in fraction[..len].bytes().take(9).enumerate() {
+            nanos += u32::from(digit - b'0') * 10u32.pow(8 - i as u32);
+        }
+
+        rest = &fraction[len..];
+    }
+
+    let offset = match rest.as_bytes() {
+        [b'Z' | b'z'] => 0,
+        [sign @ (b'+' | b'-'), _, _, b':', _, _] => {
+            let hours = digits(&rest.as_bytes()[1..3])?;
+            let minutes = digits(&rest.as_bytes()[4..6])?;
+
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+
+            let offset = i64::from(hours * 3600 + minutes * 60);
+            if *sign == b'-' { -offset } else { offset }
+        }
+        _ => return None,
+    };
+
+    let seconds = days_from_civil(year, month, day) * 86400
+        + i64::from(hour * 3600 + minute * 60 + second)
+        - offset;
+
+    let time = if seconds >= 0 {
+        SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(seconds as u64))?
+    } else {
+        SystemTime::UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.unsigned_abs()))?
+    };
+
+    time.checked_add(Duration::from_nanos(nanos.into()))
+}
+
+/// Parse a fixed-length string of ASCII digits.
+fn digits(raw: &[u8]) -> Option<u32> {
+    raw.iter().try_fold(0, |acc, &b| {
+        if b.is_ascii_digit() {
+            Some(acc * 10 + u32::from(b - b'0'))
+        } else {
+            None
+        }
+    })
+}
+
+fn is_leap_year(year: u32) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Number of days since 1970-01-01 for the given date in the proleptic Gregorian calendar.
+fn days_from_civil(year: u32, month: u32, day: u32) -> i64 {
+    // See http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unix(seconds: i64, nanos: u32) -> SystemTime {
+        let time = if seconds >= 0 {
+            SystemTime::UNIX_EPOCH + Duration::from_secs(seconds as u64)
+        } else {
+            SystemTime::UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs())
+        };
+
+        time + Duration::from_nanos(nanos.into())
+    }
+
+    #[test]
+    fn rfc3339() {
+        assert_eq!(parse_rfc3339("1970-01-01T00:00:00Z"), Some(unix(0, 0)));
+        assert_eq!(
+            parse_rfc3339("2020-06-12T17:53:00Z"),
+            Some(unix(1_591_984_380, 0))
+        );
+        assert_eq!(
+            parse_rfc3339("2020-06-12t19:53:00.25+02:00"),
+            Some(unix(1_591_984_380, 250_000_000))
+        );
+        assert_eq!(
+            parse_rfc3339("2024-02-29 00:00:00.123456789123-00:30"),
+            Some(unix(1_709_166_600, 123_456_789))
+        );
+        assert_eq!(parse_rfc3339("1969-12-31T23:59:59Z"), Some(unix(-1, 0)));
+
+        for invalid in [
+            "",
+            "2020-06-12",
+            "2020-06-12T17:53:00",
+            "2020-06-12T17:53:00+0200",
+            "2020-13-12T17:53:00Z",
+            "2023-02-29T17:53:00Z",
+            "2020-06-12T24:00:00Z",
+            "2020-06-12T17:53:00.Z",
+            "+020-06-12T17:53:00Z",
+            "2020-06-12T17:53:00Zfoo",
+            "2020-06-12T17:53:0\u{e9}Z",
+        ] {
+            assert_eq!(parse_rfc3339(invalid), None, "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn timestamp() {
+        let timestamp =
+            Timestamp::from_value(String::from("2020-06-12T17:53:00Z"), "Last-Modified").unwrap();
+
+        assert_eq!(timestamp.raw(), "2020-06-12T17:53:00Z");
+        assert_eq!(timestamp, unix(1_591_984_380, 0));
+        assert!(timestamp < unix(1_591_984_381, 0));
+
+        #[cfg(feature = "time")]
+        assert_eq!(timestamp.time_datetime().unix_timestamp(), 1_591_984_380);
+
+        #[cfg(feature = "jiff")]
+        assert_eq!(timestamp.jiff_timestamp().as_second(), 1_591_984_380);
+
+        assert!(Timestamp::from_value(String::from("foo"), "Last-Modified").is_err());
+    }
+}
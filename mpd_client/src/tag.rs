@@ -26,10 +26,16 @@ use mpd_protocol::command::Argument;
 /// are encountered using the `Other` variant. Additionally the enum is marked as non-exhaustive,
 /// so additional tags may be added without breaking compatibility.
 ///
+/// The exception are [`Song`]s: Since unrecognized tags can't be told apart from other song
+/// attributes, fields with unrecognized names are stored in [`Song::other_fields`] instead of
+/// [`Song::tags`].
+///
 /// The equality is checked using the string representation, so `Other` variants are
 /// forward-compatible with new variants being added.
 ///
 /// [`Song`]: crate::responses::Song
+/// [`Song::other_fields`]: crate::responses::Song::other_fields
+/// [`Song::tags`]: crate::responses::Song::tags
 /// [MusicBrainz]: https://musicbrainz.org
 #[derive(Clone, Debug)]
 #[allow(missing_docs)]
@@ -52,19 +58,24 @@ pub enum Tag {
     Grouping,
     Label,
     Location,
+    Mood,
     Movement,
     MovementNumber,
     MusicBrainzArtistId,
     MusicBrainzRecordingId,
     MusicBrainzReleaseArtistId,
+    MusicBrainzReleaseGroupId,
     MusicBrainzReleaseId,
     MusicBrainzTrackId,
     MusicBrainzWorkId,
     Name,
     OriginalDate,
     Performer,
+    ShowMovement,
     Title,
+    TitleSort,
     Track,
+    Version,
     Work,
     /// Catch-all variant that contains the raw tag string when it doesn't match any other
     /// variants, but is valid.
@@ -99,19 +110,24 @@ impl Tag {
             Tag::Grouping => "Grouping",
             Tag::Label => "Label",
             Tag::Location => "Location",
+            Tag::Mood => "Mood",
             Tag::Movement => "Movement",
             Tag::MovementNumber => "MovementNumber",
             Tag::MusicBrainzArtistId => "MUSICBRAINZ_ARTISTID",
             Tag::MusicBrainzRecordingId => "MUSICBRAINZ_TRACKID",
             Tag::MusicBrainzReleaseArtistId => "MUSICBRAINZ_ALBUMARTISTID",
+            Tag::MusicBrainzReleaseGroupId => "MUSICBRAINZ_RELEASEGROUPID",
             Tag::MusicBrainzReleaseId => "MUSICBRAINZ_ALBUMID",
             Tag::MusicBrainzTrackId => "MUSICBRAINZ_RELEASETRACKID",
             Tag::MusicBrainzWorkId => "MUSICBRAINZ_WORKID",
             Tag::Name => "Name",
             Tag::OriginalDate => "OriginalDate",
             Tag::Performer => "Performer",
+            Tag::ShowMovement => "ShowMovement",
             Tag::Title => "Title",
+            Tag::TitleSort => "TitleSort",
             Tag::Track => "Track",
+            Tag::Version => "Version",
             Tag::Work => "Work",
         })
     }
@@ -159,19 +175,24 @@ impl<'a> TryFrom<&'a str> for Tag {
             "Grouping" => Self::Grouping,
             "Label" => Self::Label,
             "Location" => Self::Location,
+            "Mood" => Self::Mood,
             "Movement" => Self::Movement,
             "MovementNumber" => Self::MovementNumber,
             "MUSICBRAINZ_ALBUMARTISTID" => Self::MusicBrainzReleaseArtistId,
             "MUSICBRAINZ_ALBUMID" => Self::MusicBrainzReleaseId,
             "MUSICBRAINZ_ARTISTID" => Self::MusicBrainzArtistId,
+            "MUSICBRAINZ_RELEASEGROUPID" => Self::MusicBrainzReleaseGroupId,
             "MUSICBRAINZ_RELEASETRACKID" => Self::MusicBrainzTrackId,
             "MUSICBRAINZ_TRACKID" => Self::MusicBrainzRecordingId,
             "MUSICBRAINZ_WORKID" => Self::MusicBrainzWorkId,
             "Name" => Self::Name,
             "OriginalDate" => Self::OriginalDate,
             "Performer" => Self::Performer,
+            "ShowMovement" => Self::ShowMovement,
             "Title" => Self::Title,
+            "TitleSort" => Self::TitleSort,
             "Track" => Self::Track,
+            "Version" => Self::Version,
             "Work" => Self::Work
         }
 
@@ -217,6 +238,22 @@ impl Argument for Tag {
     }
 }
 
+/// Tags are serialized as their name, as used by MPD.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Tag {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Tag {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Tag::try_from(&*raw).map_err(serde::de::Error::custom)
+    }
+}
+
 /// Errors that may occur when attempting to create a [`Tag`].
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum TagError {
@@ -254,6 +291,11 @@ mod tests {
 
         // case-insensitive
         assert_eq!(Tag::try_from("artist"), Ok(Tag::Artist));
+        assert_eq!(
+            Tag::try_from("MUSICBRAINZ_RELEASEGROUPID"),
+            Ok(Tag::MusicBrainzReleaseGroupId)
+        );
+        assert_eq!(Tag::try_from("titlesort"), Ok(Tag::TitleSort));
 
         // unrecognized but valid tag
         assert_eq!(Tag::try_from("foo"), Ok(Tag::Other(Box::from("foo"))));
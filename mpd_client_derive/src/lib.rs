@@ -0,0 +1,385 @@
+#![warn(
+    missing_debug_implementations,
+    missing_docs,
+    rust_2018_idioms,
+    unreachable_pub
+)]
+#![deny(rustdoc::broken_intra_doc_links)]
+#![forbid(unsafe_code)]
+
+//! Derive macros for [`mpd_client`](https://docs.rs/mpd_client).
+//!
+//! Don't use this crate directly, enable the `derive` feature of `mpd_client` instead, which
+//! re-exports the macros as `mpd_client::commands::MpdCommand` and
+//! `mpd_client::responses::FromFrame`.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{ToTokens, quote};
+use syn::{
+    Data, DeriveInput, Error, Fields, GenericArgument, Ident, LitStr, PathArguments, Type,
+    parse_macro_input,
+};
+
+/// Derive `mpd_client::commands::Command` for a struct.
+///
+/// The name of the command is given using the `#[mpd(command = "...")]` attribute on the struct.
+/// All fields are rendered as arguments to the command in declaration order, and must implement
+/// `mpd_client::protocol::command::Argument`. Fields of type `Option<T>` are only rendered if
+/// they are `Some`, and fields marked with `#[mpd(skip)]` are ignored.
+///
+/// Further struct attributes:
+///
+///  - `#[mpd(response = Type)]`: The response type, which must implement
+///    `mpd_client::responses::FromFrame`. If not given, the response is `()` and the frame is
+///    ignored.
+///  - `#[mpd(min_version = "0.24.0")]`: The minimum protocol version the server must support.
+///
+/// ```
+/// use mpd_client::{
+///     commands::{Command, MpdCommand},
+///     responses::FromFrame,
+/// };
+///
+/// #[derive(FromFrame)]
+/// struct StickerValue {
+///     sticker: String,
+/// }
+///
+/// #[derive(MpdCommand)]
+/// #[mpd(command = "sticker", response = StickerValue)]
+/// struct GetRating {
+///     #[mpd(skip)]
+///     _unused: (),
+///     action: &'static str,
+///     kind: &'static str,
+///     uri: String,
+///     name: Option<&'static str>,
+/// }
+///
+/// let command = GetRating {
+///     _unused: (),
+///     action: "get",
+///     kind: "song",
+///     uri: String::from("foo.flac"),
+///     name: Some("rating"),
+/// };
+///
+/// assert_eq!(
+///     command.command(),
+///     mpd_client::protocol::Command::new("sticker")
+///         .argument("get")
+///         .argument("song")
+///         .argument("foo.flac")
+///         .argument("rating"),
+/// );
+/// ```
+#[proc_macro_derive(MpdCommand, attributes(mpd))]
+pub fn derive_mpd_command(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    mpd_command(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derive `mpd_client::responses::FromFrame` for a struct with named fields.
+///
+/// Each field is parsed from the field of the response frame with the same name, using the
+/// `mpd_client::responses::FromFieldValue` implementation of its type. Use
+/// `#[mpd(rename = "...")]` to read a differently named field. Fields of type `Option<T>` are
+/// optional, and fields of type `Vec<T>` collect all occurrences of the field. Fields marked with
+/// `#[mpd(skip)]` are initialized using their `Default` implementation.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use mpd_client::responses::FromFrame;
+///
+/// #[derive(Debug, FromFrame)]
+/// struct ReplayGain {
+///     #[mpd(rename = "replay_gain_mode")]
+///     mode: String,
+///     #[mpd(rename = "Time")]
+///     duration: Option<Duration>,
+///     #[mpd(rename = "Genre")]
+///     genres: Vec<String>,
+/// }
+/// ```
+#[proc_macro_derive(FromFrame, attributes(mpd))]
+pub fn derive_from_frame(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_frame(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn mpd_command(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let mut command = None;
+    let mut response = None;
+    let mut min_version = None;
+
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("mpd")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("command") {
+                command = Some(meta.value()?.parse::<LitStr>()?);
+            } else if meta.path.is_ident("response") {
+                response = Some(meta.value()?.parse::<Type>()?);
+            } else if meta.path.is_ident("min_version") {
+                let lit = meta.value()?.parse::<LitStr>()?;
+                min_version = Some(parse_version(&lit)?);
+            } else {
+                return Err(meta.error("unknown attribute"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    let Some(command) = command else {
+        return Err(Error::new(
+            Span::call_site(),
+            "missing `#[mpd(command = \"...\")]` attribute",
+        ));
+    };
+
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            Span::call_site(),
+            "`MpdCommand` can only be derived for structs",
+        ));
+    };
+
+    let mut arguments = Vec::new();
+
+    for (index, field) in data.fields.iter().enumerate() {
+        if field_options(&field.attrs)?.skip {
+            continue;
+        }
+
+        let member = match &field.ident {
+            Some(ident) => ident.to_token_stream(),
+            None => syn::Index::from(index).to_token_stream(),
+        };
+
+        if option_inner(&field.ty).is_some() {
+            arguments.push(quote! {
+                if let ::core::option::Option::Some(argument) = &self.#member {
+                    command = command.argument(argument);
+                }
+            });
+        } else {
+            arguments.push(quote! {
+                command = command.argument(&self.#member);
+            });
+        }
+    }
+
+    let (response_type, frame, response) = match response {
+        Some(ty) => (
+            ty.to_token_stream(),
+            quote! { frame },
+            quote! { <#ty as ::mpd_client::responses::FromFrame>::from_frame(frame) },
+        ),
+        None => (
+            quote! { () },
+            quote! { _frame },
+            quote! { ::core::result::Result::Ok(()) },
+        ),
+    };
+
+    let min_version = min_version.map(|(major, minor, patch)| {
+        quote! {
+            fn min_protocol_version(
+                &self,
+            ) -> ::core::option::Option<::mpd_client::client::ProtocolVersion> {
+                ::core::option::Option::Some(::mpd_client::client::ProtocolVersion::new(
+                    #major, #minor, #patch,
+                ))
+            }
+        }
+    });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::mpd_client::commands::Command for #name #ty_generics #where_clause {
+            type Response = #response_type;
+
+            fn command(&self) -> ::mpd_client::protocol::Command {
+                #[allow(unused_mut)]
+                let mut command = ::mpd_client::protocol::Command::new(#command);
+                #(#arguments)*
+                command
+            }
+
+            #min_version
+
+            fn response(
+                self,
+                #frame: ::mpd_client::protocol::response::Frame,
+            ) -> ::core::result::Result<Self::Response, ::mpd_client::responses::TypedResponseError>
+            {
+                #response
+            }
+        }
+    })
+}
+
+fn from_frame(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "`FromFrame` can only be derived for structs with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                Span::call_site(),
+                "`FromFrame` can only be derived for structs",
+            ));
+        }
+    };
+
+    let mut initializers = Vec::new();
+
+    for field in fields {
+        let options = field_options(&field.attrs)?;
+        let ident = field.ident.as_ref().unwrap();
+
+        if options.skip {
+            initializers.push(quote! { #ident: ::core::default::Default::default() });
+            continue;
+        }
+
+        let key = options
+            .rename
+            .unwrap_or_else(|| LitStr::new(&unraw(ident), ident.span()));
+
+        let value = if let Some(inner) = option_inner(&field.ty) {
+            quote! {
+                match frame.get(#key) {
+                    ::core::option::Option::Some(value) => ::core::option::Option::Some(
+                        <#inner as ::mpd_client::responses::FromFieldValue>::from_value(value, #key)?,
+                    ),
+                    ::core::option::Option::None => ::core::option::Option::None,
+                }
+            }
+        } else if let Some(inner) = generic_inner(&field.ty, "Vec") {
+            quote! {{
+                let mut values = ::std::vec::Vec::new();
+                while let ::core::option::Option::Some(value) = frame.get(#key) {
+                    values.push(
+                        <#inner as ::mpd_client::responses::FromFieldValue>::from_value(value, #key)?,
+                    );
+                }
+                values
+            }}
+        } else {
+            let ty = &field.ty;
+            quote! {{
+                let value = frame
+                    .get(#key)
+                    .ok_or_else(|| ::mpd_client::responses::TypedResponseError::missing(#key))?;
+                <#ty as ::mpd_client::responses::FromFieldValue>::from_value(value, #key)?
+            }}
+        };
+
+        initializers.push(quote! { #ident: #value });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::mpd_client::responses::FromFrame for #name #ty_generics #where_clause {
+            fn from_frame(
+                #[allow(unused_mut)] mut frame: ::mpd_client::protocol::response::Frame,
+            ) -> ::core::result::Result<Self, ::mpd_client::responses::TypedResponseError> {
+                ::core::result::Result::Ok(Self {
+                    #(#initializers,)*
+                })
+            }
+        }
+    })
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    skip: bool,
+    rename: Option<LitStr>,
+}
+
+fn field_options(attrs: &[syn::Attribute]) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+
+    for attr in attrs.iter().filter(|a| a.path().is_ident("mpd")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                options.skip = true;
+            } else if meta.path.is_ident("rename") {
+                options.rename = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("unknown attribute"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(options)
+}
+
+fn parse_version(lit: &LitStr) -> syn::Result<(u32, u32, u32)> {
+    let value = lit.value();
+    let mut parts = value.split('.').map(str::parse::<u32>);
+
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(major)), Some(Ok(minor)), patch, None) => {
+            let patch = match patch {
+                None => 0,
+                Some(Ok(patch)) => patch,
+                Some(Err(_)) => return Err(Error::new(lit.span(), "invalid protocol version")),
+            };
+
+            Ok((major, minor, patch))
+        }
+        _ => Err(Error::new(lit.span(), "invalid protocol version")),
+    }
+}
+
+/// If the type is `Option<T>`, return `T`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    generic_inner(ty, "Option")
+}
+
+/// If the type is a path ending in `name<T>`, return `T`.
+fn generic_inner<'a>(ty: &'a Type, name: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    let segment = path.path.segments.last()?;
+
+    if segment.ident != name {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+
+    match arguments.args.first()? {
+        GenericArgument::Type(ty) if arguments.args.len() == 1 => Some(ty),
+        _ => None,
+    }
+}
+
+fn unraw(ident: &Ident) -> String {
+    let ident = ident.to_string();
+    ident.strip_prefix("r#").map(String::from).unwrap_or(ident)
+}
@@ -11,6 +11,7 @@ use std::{
     borrow::Cow,
     error::Error,
     fmt::{self, Debug},
+    mem,
     time::Duration,
 };
 
@@ -131,6 +132,58 @@ impl CommandList {
         self.0.len()
     }
 
+    /// Get the number of bytes the commands take up when rendered, including their terminating
+    /// newlines, but excluding the command list delimiters.
+    ///
+    /// ```
+    /// # use mpd_protocol::command::{Command, CommandList};
+    /// let list = CommandList::new(Command::new("foo")).command(Command::new("bar"));
+    /// assert_eq!(list.size(), 8);
+    /// ```
+    pub fn size(&self) -> usize {
+        self.0.iter().map(|command| command.0.len() + 1).sum()
+    }
+
+    /// Split the command list into multiple lists, so that the commands in each list take up at
+    /// most `max_size` bytes when rendered (including their terminating newlines, but excluding
+    /// the command list delimiters).
+    ///
+    /// This is useful to avoid exceeding the maximum command list size of the server. The order
+    /// of the commands is preserved. A single command which is larger than `max_size` is placed
+    /// into a list on its own.
+    ///
+    /// ```
+    /// # use mpd_protocol::command::{Command, CommandList};
+    /// let list = CommandList::new(Command::new("foo"))
+    ///     .command(Command::new("bar"))
+    ///     .command(Command::new("baz"));
+    ///
+    /// let chunks = list.chunks_by_size(8);
+    /// assert_eq!(chunks.len(), 2);
+    /// assert_eq!(chunks[0].len(), 2);
+    /// assert_eq!(chunks[1].len(), 1);
+    /// ```
+    pub fn chunks_by_size(self, max_size: usize) -> Vec<CommandList> {
+        let mut chunks = Vec::new();
+        let mut current: Vec<Command> = Vec::new();
+        let mut current_size = 0;
+
+        for command in self.0 {
+            let size = command.0.len() + 1;
+
+            if !current.is_empty() && current_size + size > max_size {
+                chunks.push(CommandList(mem::take(&mut current)));
+                current_size = 0;
+            }
+
+            current_size += size;
+            current.push(command);
+        }
+
+        chunks.push(CommandList(current));
+        chunks
+    }
+
     pub(crate) fn render(mut self) -> BytesMut {
         if self.len() == 1 {
             let mut buf = self.0.pop().unwrap().0;
@@ -156,6 +209,15 @@ impl CommandList {
     }
 }
 
+impl IntoIterator for CommandList {
+    type Item = Command;
+    type IntoIter = std::vec::IntoIter<Command>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
 impl Extend<Command> for CommandList {
     fn extend<T: IntoIterator<Item = Command>>(&mut self, iter: T) {
         self.0.extend(iter);
@@ -370,6 +432,22 @@ mod test {
         assert_eq!(command.0, "foo bar");
     }
 
+    #[test]
+    fn command_list_chunks() {
+        let list = CommandList::new(Command::new("a"))
+            .command(Command::new("bb"))
+            .command(Command::new("toolong"))
+            .command(Command::new("c"));
+
+        let chunks = list.chunks_by_size(5);
+        let lengths = chunks.iter().map(CommandList::len).collect::<Vec<_>>();
+        assert_eq!(lengths, [2, 1, 1]);
+        assert_eq!(chunks[1].0[0].0, "toolong");
+
+        let list = CommandList::new(Command::new("a")).command(Command::new("b"));
+        assert_eq!(list.clone().chunks_by_size(usize::MAX), [list]);
+    }
+
     #[test]
     fn argument_escaping() {
         assert_eq!(escape_argument("status"), "status");
@@ -283,12 +283,32 @@ impl<IO> AsyncConnection<IO> {
     ///  - A malformed greeting is received
     ///  - The connection is closed before a complete greeting could be read
     #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
-    #[tracing::instrument(skip_all, err)]
-    pub async fn connect(mut io: IO) -> Result<AsyncConnection<IO>, MpdProtocolError>
+    pub async fn connect(io: IO) -> Result<AsyncConnection<IO>, MpdProtocolError>
     where
         IO: AsyncRead + Unpin,
     {
-        let mut recv_buf = BytesMut::with_capacity(DEFAULT_BUFFER_CAPACITY);
+        Self::connect_with_buffer_capacity(io, DEFAULT_BUFFER_CAPACITY).await
+    }
+
+    /// Connect to an MPD server asynchronously, using the given initial capacity for the receive
+    /// buffer.
+    ///
+    /// The buffer grows as needed when receiving large responses, but a larger initial capacity
+    /// avoids repeated reallocations if such responses are expected.
+    ///
+    /// # Errors
+    ///
+    /// This will return errors in the same conditions as [`AsyncConnection::connect`].
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    #[tracing::instrument(skip(io), err)]
+    pub async fn connect_with_buffer_capacity(
+        mut io: IO,
+        capacity: usize,
+    ) -> Result<AsyncConnection<IO>, MpdProtocolError>
+    where
+        IO: AsyncRead + Unpin,
+    {
+        let mut recv_buf = BytesMut::with_capacity(capacity);
 
         let protocol_version = loop {
             let read = io.read_buf(&mut recv_buf).await?;
@@ -608,6 +628,16 @@ mod tests_async {
         assert_eq!(connection.protocol_version(), "0.23.3");
     }
 
+    #[tokio::test]
+    async fn connect_with_buffer_capacity() {
+        let io = MockBuilder::new().read(b"OK MPD 0.23.3\n").build();
+        let connection = AsyncConnection::connect_with_buffer_capacity(io, 65536)
+            .await
+            .unwrap();
+        assert_eq!(connection.protocol_version(), "0.23.3");
+        assert!(connection.0.recv_buf.capacity() >= 65536);
+    }
+
     #[tokio::test]
     async fn connect_eof() {
         let io = MockBuilder::new().read(b"OK MPD 0.23.3").build(); // no newline
@@ -2,7 +2,15 @@
 
 pub mod frame;
 
-use std::{collections::HashSet, fmt, iter::FusedIterator, mem, slice, sync::Arc, vec};
+use std::{
+    collections::HashSet,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
+    mem, slice,
+    sync::Arc,
+    vec,
+};
 
 use bytes::{Buf, BytesMut};
 use tracing::trace;
@@ -32,6 +40,24 @@ impl Response {
         }
     }
 
+    /// Construct a response from the given successful frames and optional error.
+    ///
+    /// This is useful for splitting up the response to a command list, e.g. when the list was
+    /// assembled from multiple independent commands.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frames` is empty and `error` is `None`, since every response contains at least
+    /// one frame or an error.
+    pub fn new(frames: Vec<Frame>, error: Option<Error>) -> Self {
+        assert!(
+            !frames.is_empty() || error.is_some(),
+            "response must contain at least one frame or an error"
+        );
+
+        Self { frames, error }
+    }
+
     /// Returns `true` if the response contains an error.
     ///
     /// Even if this returns `true`, there may still be successful frames in the response when the
@@ -370,6 +396,108 @@ pub struct Error {
     pub message: Box<str>,
 }
 
+impl Error {
+    /// Get the typed [error code][ErrorCode] of this error.
+    pub fn kind(&self) -> ErrorCode {
+        ErrorCode::from(self.code)
+    }
+}
+
+/// Typed representation of the error codes that can be returned by MPD.
+///
+/// See [the MPD source][mpd-error-def] for the definitions.
+///
+/// Error codes are compared by their numeric value, so [`ErrorCode::Other`] containing a code
+/// which has a named variant is equal to that variant.
+///
+/// [mpd-error-def]: https://github.com/MusicPlayerDaemon/MPD/blob/master/src/protocol/Ack.hxx#L30
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// A command list related error (`ACK_ERROR_NOT_LIST`).
+    NotList,
+    /// Invalid command arguments (`ACK_ERROR_ARG`).
+    Arg,
+    /// Incorrect password (`ACK_ERROR_PASSWORD`).
+    Password,
+    /// Insufficient permissions for the command (`ACK_ERROR_PERMISSION`).
+    Permission,
+    /// Unknown command (`ACK_ERROR_UNKNOWN`).
+    Unknown,
+    /// The requested resource does not exist (`ACK_ERROR_NO_EXIST`).
+    NoExist,
+    /// The maximum playlist length was reached (`ACK_ERROR_PLAYLIST_MAX`).
+    PlaylistMax,
+    /// A system error, e.g. a failed IO operation (`ACK_ERROR_SYSTEM`).
+    System,
+    /// Loading a playlist failed (`ACK_ERROR_PLAYLIST_LOAD`).
+    PlaylistLoad,
+    /// A database update is already in progress (`ACK_ERROR_UPDATE_ALREADY`).
+    UpdateAlready,
+    /// The player is not in a state where the command can be executed (`ACK_ERROR_PLAYER_SYNC`).
+    PlayerSync,
+    /// The resource to be created already exists (`ACK_ERROR_EXIST`).
+    Exist,
+    /// An error code not recognized by this type.
+    ///
+    /// [`ErrorCode::from`] never returns this for codes which have a named variant.
+    Other(u64),
+}
+
+impl PartialEq for ErrorCode {
+    fn eq(&self, other: &Self) -> bool {
+        u64::from(*self) == u64::from(*other)
+    }
+}
+
+impl Eq for ErrorCode {}
+
+impl Hash for ErrorCode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        u64::from(*self).hash(state);
+    }
+}
+
+impl From<u64> for ErrorCode {
+    fn from(code: u64) -> Self {
+        match code {
+            1 => ErrorCode::NotList,
+            2 => ErrorCode::Arg,
+            3 => ErrorCode::Password,
+            4 => ErrorCode::Permission,
+            5 => ErrorCode::Unknown,
+            50 => ErrorCode::NoExist,
+            51 => ErrorCode::PlaylistMax,
+            52 => ErrorCode::System,
+            53 => ErrorCode::PlaylistLoad,
+            54 => ErrorCode::UpdateAlready,
+            55 => ErrorCode::PlayerSync,
+            56 => ErrorCode::Exist,
+            other => ErrorCode::Other(other),
+        }
+    }
+}
+
+impl From<ErrorCode> for u64 {
+    fn from(code: ErrorCode) -> Self {
+        match code {
+            ErrorCode::NotList => 1,
+            ErrorCode::Arg => 2,
+            ErrorCode::Password => 3,
+            ErrorCode::Permission => 4,
+            ErrorCode::Unknown => 5,
+            ErrorCode::NoExist => 50,
+            ErrorCode::PlaylistMax => 51,
+            ErrorCode::System => 52,
+            ErrorCode::PlaylistLoad => 53,
+            ErrorCode::UpdateAlready => 54,
+            ErrorCode::PlayerSync => 55,
+            ErrorCode::Exist => 56,
+            ErrorCode::Other(code) => code,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use assert_matches::assert_matches;
@@ -436,6 +564,28 @@ mod test {
         assert_eq!((0, Some(0)), iter.size_hint());
     }
 
+    #[test]
+    fn error_codes() {
+        for code in (0..=60).chain([u64::MAX]) {
+            assert_eq!(u64::from(ErrorCode::from(code)), code);
+        }
+
+        assert_eq!(ErrorCode::from(5), ErrorCode::Unknown);
+        assert_eq!(ErrorCode::from(50), ErrorCode::NoExist);
+        assert_eq!(ErrorCode::from(6), ErrorCode::Other(6));
+        assert_eq!(ErrorCode::Other(5), ErrorCode::Unknown);
+        assert_ne!(ErrorCode::Other(6), ErrorCode::Unknown);
+
+        let codes = HashSet::from([ErrorCode::Unknown, ErrorCode::Other(5)]);
+        assert_eq!(codes.len(), 1);
+
+        let error = Error {
+            code: 4,
+            ..Error::default()
+        };
+        assert_eq!(error.kind(), ErrorCode::Permission);
+    }
+
     #[test]
     fn simple_response() {
         let mut io = BytesMut::from("foo: bar\nOK");